@@ -0,0 +1,338 @@
+//! Forwarding `execute` to a second transfer-hook program.
+//!
+//! Token-2022 resolves a single meta list per mint, so the downstream hook's
+//! accounts are copied into mintcraft's list right after the config:
+//! the downstream program, its validation PDA, then its own extra metas with
+//! account indexes shifted to where they end up in mintcraft's `execute`.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::AccountMeta, program::invoke};
+use spl_tlv_account_resolution::{
+    account::ExtraAccountMeta, pubkey_data::PubkeyData, seeds::Seed, state::ExtraAccountMetaList,
+};
+use spl_transfer_hook_interface::instruction::{
+    execute_with_extra_account_metas, ExecuteInstruction,
+};
+use spl_type_length_value::state::TlvStateBorrowed;
+
+use crate::{MaxWalletConfig, MaxWalletError};
+
+/// Index of the downstream validation PDA in a hook's own `execute`.
+const VALIDATION_INDEX: u8 = 4;
+/// Index of the downstream program in mintcraft's `execute` (after the config).
+const PROGRAM_INDEX: u8 = 6;
+/// Index of the downstream validation PDA in mintcraft's `execute`.
+const FORWARDED_VALIDATION_INDEX: u8 = 7;
+/// How far the downstream's extra accounts move in mintcraft's `execute`.
+const EXTRA_ACCOUNT_SHIFT: u8 = FORWARDED_VALIDATION_INDEX + 1 - (VALIDATION_INDEX + 1);
+
+/// Number of accounts the downstream segment occupies after the config.
+pub fn segment_len(config: &MaxWalletConfig) -> usize {
+    if config.downstream_hook == Pubkey::default() {
+        0
+    } else {
+        2 + config.downstream_meta_count as usize
+    }
+}
+
+/// Extra metas stored in an `ExtraAccountMetaList` account, empty if it was
+/// never initialized.
+pub fn read_extra_account_metas(account: &AccountInfo) -> Result<Vec<ExtraAccountMeta>> {
+    if account.data_is_empty() {
+        return Ok(Vec::new());
+    }
+    let data = account
+        .try_borrow_data()
+        .map_err(|_| MaxWalletError::AccountBorrowFailed)?;
+    let state = TlvStateBorrowed::unpack(&data)
+        .map_err(|_| MaxWalletError::InvalidDownstreamExtraAccountMetas)?;
+    let metas = ExtraAccountMetaList::unpack_with_tlv_state::<ExecuteInstruction>(&state)
+        .map_err(|_| MaxWalletError::InvalidDownstreamExtraAccountMetas)?;
+    Ok(metas.data().to_vec())
+}
+
+/// The segment currently stored in mintcraft's own meta list, kept as is when
+/// the list is rewritten for other reasons.
+pub fn stored_segment(
+    config: &MaxWalletConfig,
+    extra_meta_info: &AccountInfo,
+) -> Result<Vec<ExtraAccountMeta>> {
+    let len = segment_len(config);
+    if len == 0 {
+        return Ok(Vec::new());
+    }
+    let metas = read_extra_account_metas(extra_meta_info)?;
+    // The config meta comes first
+    let segment = metas
+        .get(1..1 + len)
+        .ok_or(MaxWalletError::MissingDownstreamAccounts)?;
+    Ok(segment.to_vec())
+}
+
+/// The segment to store in mintcraft's meta list for `program`, whose own
+/// metas are `downstream_metas`.
+pub fn segment(
+    program: &Pubkey,
+    validation: &Pubkey,
+    downstream_metas: &[ExtraAccountMeta],
+) -> Result<Vec<ExtraAccountMeta>> {
+    let mut segment = vec![
+        ExtraAccountMeta::new_with_pubkey(program, false, false)
+            .map_err(|_| MaxWalletError::ExtraAccountMetaSerialization)?,
+        ExtraAccountMeta::new_with_pubkey(validation, false, false)
+            .map_err(|_| MaxWalletError::ExtraAccountMetaSerialization)?,
+    ];
+    for meta in downstream_metas {
+        segment.push(relocate(meta)?);
+    }
+    Ok(segment)
+}
+
+fn shift_index(index: u8) -> Result<u8> {
+    let shifted = match index {
+        VALIDATION_INDEX => Some(FORWARDED_VALIDATION_INDEX),
+        index if index > VALIDATION_INDEX => index.checked_add(EXTRA_ACCOUNT_SHIFT),
+        index => Some(index),
+    };
+    Ok(shifted.ok_or(MaxWalletError::InvalidDownstreamExtraAccountMetas)?)
+}
+
+fn shift_seed(seed: Seed) -> Result<Seed> {
+    Ok(match seed {
+        Seed::AccountKey { index } => Seed::AccountKey {
+            index: shift_index(index)?,
+        },
+        Seed::AccountData {
+            account_index,
+            data_index,
+            length,
+        } => Seed::AccountData {
+            account_index: shift_index(account_index)?,
+            data_index,
+            length,
+        },
+        seed => seed,
+    })
+}
+
+/// Rewrites a downstream meta so it resolves to the same account from within
+/// mintcraft's list. PDAs of the downstream program become external PDAs.
+fn relocate(meta: &ExtraAccountMeta) -> Result<ExtraAccountMeta> {
+    let is_signer = bool::from(meta.is_signer);
+    let is_writable = bool::from(meta.is_writable);
+    let invalid = |_| MaxWalletError::InvalidDownstreamExtraAccountMetas;
+
+    let relocated = match meta.discriminator {
+        0 => Ok(*meta),
+        2 => {
+            let key_data = match PubkeyData::unpack(&meta.address_config).map_err(invalid)? {
+                PubkeyData::AccountData {
+                    account_index,
+                    data_index,
+                } => PubkeyData::AccountData {
+                    account_index: shift_index(account_index)?,
+                    data_index,
+                },
+                key_data => key_data,
+            };
+            ExtraAccountMeta::new_with_pubkey_data(&key_data, is_signer, is_writable)
+        }
+        discriminator => {
+            let program_index = match discriminator {
+                1 => PROGRAM_INDEX,
+                discriminator if discriminator >= 1 << 7 => shift_index(discriminator - (1 << 7))?,
+                _ => return err!(MaxWalletError::InvalidDownstreamExtraAccountMetas),
+            };
+            let seeds = Seed::unpack_address_config(&meta.address_config)
+                .map_err(invalid)?
+                .into_iter()
+                .map(shift_seed)
+                .collect::<Result<Vec<_>>>()?;
+            ExtraAccountMeta::new_external_pda_with_seeds(
+                program_index,
+                &seeds,
+                is_signer,
+                is_writable,
+            )
+        }
+    };
+    Ok(relocated.map_err(invalid)?)
+}
+
+/// CPI into the downstream hook with the transfer's accounts and the
+/// downstream segment of `remaining_accounts`.
+pub fn forward_execute<'info>(
+    config: &MaxWalletConfig,
+    transfer_accounts: [&AccountInfo<'info>; 4],
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    let segment = remaining_accounts
+        .get(..segment_len(config))
+        .ok_or(MaxWalletError::MissingDownstreamAccounts)?;
+    let [program, validation, extras @ ..] = segment else {
+        return err!(MaxWalletError::MissingDownstreamAccounts);
+    };
+    require_keys_eq!(
+        program.key(),
+        config.downstream_hook,
+        MaxWalletError::InvalidDownstreamHook
+    );
+
+    let [source, mint, destination, authority] = transfer_accounts;
+    let extra_metas = extras
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        })
+        .collect::<Vec<_>>();
+    let instruction = execute_with_extra_account_metas(
+        program.key,
+        source.key,
+        mint.key,
+        destination.key,
+        authority.key,
+        validation.key,
+        &extra_metas,
+        amount,
+    );
+
+    let mut account_infos = vec![
+        source.clone(),
+        mint.clone(),
+        destination.clone(),
+        authority.clone(),
+        validation.clone(),
+    ];
+    account_infos.extend(extras.iter().cloned());
+    account_infos.push(program.clone());
+    invoke(&instruction, &account_infos)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seeds_of(meta: &ExtraAccountMeta) -> Vec<Seed> {
+        Seed::unpack_address_config(&meta.address_config).unwrap()
+    }
+
+    #[test]
+    fn shift_index_moves_accounts_after_the_validation_pda() {
+        // source, mint, destination and authority keep their place
+        for index in 0..VALIDATION_INDEX {
+            assert_eq!(shift_index(index).unwrap(), index);
+        }
+        assert_eq!(
+            shift_index(VALIDATION_INDEX).unwrap(),
+            FORWARDED_VALIDATION_INDEX
+        );
+        assert_eq!(shift_index(5).unwrap(), 8);
+        assert_eq!(shift_index(9).unwrap(), 12);
+        assert!(shift_index(u8::MAX).is_err());
+    }
+
+    #[test]
+    fn relocate_keeps_fixed_pubkeys() {
+        let meta = ExtraAccountMeta::new_with_pubkey(&Pubkey::new_unique(), false, true).unwrap();
+        assert_eq!(relocate(&meta).unwrap(), meta);
+    }
+
+    #[test]
+    fn relocate_turns_own_pdas_into_external_pdas_of_the_program() {
+        let seeds = [
+            Seed::Literal {
+                bytes: b"counter".to_vec(),
+            },
+            Seed::AccountKey { index: 1 },
+            Seed::AccountData {
+                account_index: 5,
+                data_index: 32,
+                length: 32,
+            },
+        ];
+        let meta = ExtraAccountMeta::new_with_seeds(&seeds, false, true).unwrap();
+
+        let relocated = relocate(&meta).unwrap();
+        assert_eq!(relocated.discriminator, (1 << 7) + PROGRAM_INDEX);
+        assert!(bool::from(relocated.is_writable));
+        assert_eq!(
+            seeds_of(&relocated),
+            vec![
+                seeds[0].clone(),
+                Seed::AccountKey { index: 1 },
+                Seed::AccountData {
+                    account_index: 8,
+                    data_index: 32,
+                    length: 32,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn relocate_shifts_external_pda_program_and_seeds() {
+        let seeds = [Seed::AccountKey {
+            index: VALIDATION_INDEX,
+        }];
+        let meta = ExtraAccountMeta::new_external_pda_with_seeds(5, &seeds, false, false).unwrap();
+
+        let relocated = relocate(&meta).unwrap();
+        assert_eq!(relocated.discriminator, (1 << 7) + 8);
+        assert_eq!(
+            seeds_of(&relocated),
+            vec![Seed::AccountKey {
+                index: FORWARDED_VALIDATION_INDEX
+            }]
+        );
+    }
+
+    #[test]
+    fn relocate_shifts_pubkey_data() {
+        let key_data = PubkeyData::AccountData {
+            account_index: 6,
+            data_index: 0,
+        };
+        let meta = ExtraAccountMeta::new_with_pubkey_data(&key_data, false, false).unwrap();
+
+        let relocated = relocate(&meta).unwrap();
+        assert_eq!(relocated.discriminator, 2);
+        assert_eq!(
+            PubkeyData::unpack(&relocated.address_config).unwrap(),
+            PubkeyData::AccountData {
+                account_index: 9,
+                data_index: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn relocate_rejects_unknown_discriminators() {
+        let mut meta =
+            ExtraAccountMeta::new_with_pubkey(&Pubkey::new_unique(), false, false).unwrap();
+        meta.discriminator = 3;
+        assert!(relocate(&meta).is_err());
+    }
+
+    #[test]
+    fn segment_starts_with_program_and_validation() {
+        let program = Pubkey::new_unique();
+        let validation = Pubkey::new_unique();
+        let extra = ExtraAccountMeta::new_with_pubkey(&Pubkey::new_unique(), false, false).unwrap();
+
+        let segment = segment(&program, &validation, &[extra]).unwrap();
+        assert_eq!(segment.len(), 3);
+        assert_eq!(
+            segment[0],
+            ExtraAccountMeta::new_with_pubkey(&program, false, false).unwrap()
+        );
+        assert_eq!(
+            segment[1],
+            ExtraAccountMeta::new_with_pubkey(&validation, false, false).unwrap()
+        );
+        assert_eq!(segment[2], extra);
+    }
+}
@@ -3,73 +3,290 @@
 use anchor_lang::prelude::*;
 use anchor_lang::AccountDeserialize;
 use anchor_lang::solana_program::{
+    instruction::Instruction,
     program::invoke_signed,
     program_pack::Pack,
     system_instruction,
 };
-use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token_2022::Token2022;
-use anchor_spl::token_interface::TokenAccount;
-use spl_tlv_account_resolution::{
-    account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList,
+use anchor_spl::associated_token::{get_associated_token_address_with_program_id, AssociatedToken};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, state::ExtraAccountMetaList};
+use spl_token_2022::instruction::{
+    initialize_mint2, initialize_mint_close_authority, initialize_non_transferable_mint,
+    initialize_permanent_delegate, mint_to, set_authority, thaw_account, AuthorityType,
 };
-use spl_token_2022::instruction::{initialize_mint2, mint_to};
 use spl_token_2022::{
-    extension::StateWithExtensions,
-    state::{Account as SplTokenAccount, Mint as SplMint},
+    extension::{
+        default_account_state, interest_bearing_mint, metadata_pointer,
+        transfer_fee::{self, TransferFeeConfig},
+        transfer_hook, BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
+    state::{Account as SplTokenAccount, AccountState, Mint as SplMint},
 };
+use spl_token_metadata_interface::state::TokenMetadata;
 use spl_transfer_hook_interface::{collect_extra_account_metas_signer_seeds, instruction::ExecuteInstruction};
 
+/// `msg!` that compiles out with the `no-log-messages` feature. Typed events
+/// are unaffected.
+macro_rules! log {
+    ($($arg:tt)*) => {
+        if cfg!(not(feature = "no-log-messages")) {
+            msg!($($arg)*);
+        }
+    };
+}
+
+mod downstream;
+mod policies;
+
+use policies::{Direction, HookTransfer};
+
 declare_id!("Hbcw8A9kdqWHt1p5C6XY1864t4PjNWa8zaiysfZMqBn4");
 
 const MAX_WALLET_CONFIG_SEED: &[u8] = b"max-wallet-config";
+const MAX_WALLET_EXEMPTION_SEED: &[u8] = b"max-wallet-exemption";
+const TRANSFER_LIST_SEED: &[u8] = b"transfer-list";
+const WALLET_STATE_SEED: &[u8] = b"wallet-state";
+const POOL_ACCOUNT_SEED: &[u8] = b"pool-account";
+const HOOK_STATS_SEED: &[u8] = b"hook-stats";
 const REFLECTION_CONFIG_SEED: &[u8] = b"reflection-config";
 const USER_CLAIM_STATE_SEED: &[u8] = b"user-claim-state";
+const MINT_AUTHORITY_SEED: &[u8] = b"mint-authority";
+const MINT_SEED: &[u8] = b"mint";
 const MAX_BPS: u16 = 10_000;
+const MAX_NAME_LENGTH: usize = 32;
+const MAX_SYMBOL_LENGTH: usize = 10;
+const MAX_URI_LENGTH: usize = 200;
+const MAX_DECIMALS: u8 = 9;
+const ALLOWED_URI_SCHEMES: [&str; 3] = ["https://", "ipfs://", "ar://"];
+const MAX_ALLOCATIONS: usize = 10;
+const MAX_MINT_SEED_LENGTH: usize = 32;
+const DEFAULT_VOLUME_WINDOW_SECS: u32 = 86_400;
+const MAX_WALLET_CONFIG_VERSION: u8 = 2;
 
 #[program]
 pub mod mintcraft {
     use super::*;
 
     pub fn initialize(_ctx: Context<Initialize>) -> Result<()> {
-        msg!("Mintcraft program initialized!");
+        log!("Mintcraft program initialized!");
         Ok(())
     }
 
-    pub fn create_token(ctx: Context<CreateToken>, args: TokenArgs) -> Result<()> {
-        msg!("Creating token: {}", args.name);
-        msg!("Symbol: {}", args.symbol);
-        msg!("Decimals: {}", args.decimals);
-        msg!("Supply: {}", args.supply);
-        msg!("URI: {}", args.uri);
+    pub fn create_token<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateToken<'info>>,
+        args: TokenArgs,
+    ) -> Result<()> {
+        log!("Creating token: {}", args.name);
+        log!("Symbol: {}", args.symbol);
+        log!("Decimals: {}", args.decimals);
+        log!("Supply: {}", args.supply);
+        log!("URI: {}", args.uri);
+
+        args.validate()?;
+        let token_program_id = ctx.accounts.token_program.key();
+        let is_token_2022 = token_program_id == spl_token_2022::id();
+        require!(
+            is_token_2022 || args.extensions.is_none(),
+            CreateTokenError::ExtensionsRequireToken2022
+        );
+        let extensions = args.extensions.clone().unwrap_or_default();
+        extensions.validate()?;
+
+        require_keys_neq!(
+            args.mint_authority,
+            Pubkey::default(),
+            CreateTokenError::InvalidAuthority
+        );
+        require!(
+            args.freeze_authority != Some(Pubkey::default()),
+            CreateTokenError::InvalidAuthority
+        );
+
+        let mint_key = ctx.accounts.mint.key();
+        require!(
+            ctx.accounts.mint.data_is_empty()
+                && ctx.accounts.mint.owner == &anchor_lang::system_program::ID,
+            CreateTokenError::MintAlreadyExists
+        );
+        let creator_key = ctx.accounts.payer.key();
+        let mint_bump = match &args.mint_seed {
+            Some(seed) => {
+                require!(
+                    seed.len() <= MAX_MINT_SEED_LENGTH,
+                    CreateTokenError::MintSeedTooLong
+                );
+                let (mint_address, bump) = Pubkey::find_program_address(
+                    &[MINT_SEED, creator_key.as_ref(), seed],
+                    ctx.program_id,
+                );
+                require_keys_eq!(mint_address, mint_key, CreateTokenError::InvalidMintAddress);
+                Some(bump)
+            }
+            None => None,
+        };
+        let mint_authority_pda = match &args.controlled_minting {
+            Some(controlled) => {
+                require!(
+                    !args.revoke_mint_authority,
+                    CreateTokenError::ConflictingMintAuthority
+                );
+                require!(
+                    controlled.max_supply >= args.supply,
+                    CreateTokenError::InvalidSupplyCap
+                );
+                Some(Pubkey::find_program_address(
+                    &[MINT_AUTHORITY_SEED, mint_key.as_ref()],
+                    ctx.program_id,
+                ))
+            }
+            None => None,
+        };
+
+        let final_mint_authority = if args.revoke_mint_authority {
+            None
+        } else if let Some((pda, _)) = mint_authority_pda {
+            Some(pda)
+        } else {
+            Some(args.mint_authority)
+        };
+        let final_freeze_authority = if args.revoke_freeze_authority {
+            None
+        } else {
+            args.freeze_authority
+        };
+        require!(
+            final_freeze_authority.is_some()
+                || extensions.default_account_state != Some(DefaultAccountStateArg::Frozen),
+            CreateTokenError::FrozenWithoutFreezeAuthority
+        );
+
+        let payer_key = ctx.accounts.payer.key();
+        let authority_key = args.mint_authority;
+
+        // Genesis distribution: the whole supply to `recipient`, or the explicit
+        // allocations with (owner, token account) pairs in the remaining accounts
+        let distribution: Vec<(AccountInfo<'info>, AccountInfo<'info>, u64)> =
+            if args.allocations.is_empty() {
+                vec![(
+                    ctx.accounts.recipient.to_account_info(),
+                    ctx.accounts.associated_token_account.to_account_info(),
+                    args.supply,
+                )]
+            } else {
+                require!(
+                    args.allocations.len() <= MAX_ALLOCATIONS,
+                    CreateTokenError::TooManyAllocations
+                );
+                require!(
+                    ctx.remaining_accounts.len() == args.allocations.len() * 2,
+                    CreateTokenError::AllocationAccountsMismatch
+                );
+                let amounts = allocation_amounts(&args.allocations, args.supply)?;
+                let mut distribution = Vec::with_capacity(amounts.len());
+                for ((pair, allocation), amount) in ctx
+                    .remaining_accounts
+                    .chunks(2)
+                    .zip(&args.allocations)
+                    .zip(amounts)
+                {
+                    require_keys_eq!(
+                        pair[0].key(),
+                        allocation.owner,
+                        CreateTokenError::AllocationAccountsMismatch
+                    );
+                    distribution.push((pair[0].clone(), pair[1].clone(), amount));
+                }
+                distribution
+            };
+        for (owner, token_account, _) in &distribution {
+            require_keys_eq!(
+                get_associated_token_address_with_program_id(
+                    owner.key,
+                    &mint_key,
+                    &token_program_id,
+                ),
+                token_account.key(),
+                CreateTokenError::InvalidRecipientTokenAccount
+            );
+        }
 
-        // Create the mint account
-        let mint_space = SplMint::LEN;
+        let metadata = TokenMetadata {
+            update_authority: Some(authority_key)
+                .try_into()
+                .map_err(|_| CreateTokenError::MetadataSerialization)?,
+            mint: mint_key,
+            name: args.name.clone(),
+            symbol: args.symbol.clone(),
+            uri: args.uri.clone(),
+            additional_metadata: vec![],
+        };
         let rent = Rent::get()?;
-        let lamports = rent.minimum_balance(mint_space);
+        let (mint_space, lamports) = if is_token_2022 {
+            // The mint is allocated for its fixed-size extensions only; the token metadata
+            // instruction reallocs the account itself, so we just pre-fund the rent.
+            let metadata_space = metadata
+                .tlv_size_of()
+                .map_err(|_| CreateTokenError::MetadataSerialization)?;
+            let mint_space =
+                ExtensionType::try_calculate_account_len::<SplMint>(&extensions.extension_types())?;
+            (mint_space, rent.minimum_balance(mint_space + metadata_space))
+        } else {
+            // Legacy SPL Token mints have no extensions, so name/symbol/uri stay off-chain
+            (SplMint::LEN, rent.minimum_balance(SplMint::LEN))
+        };
 
-        anchor_lang::solana_program::program::invoke(
-            &anchor_lang::solana_program::system_instruction::create_account(
-                &ctx.accounts.payer.key(),
-                &ctx.accounts.mint.key(),
-                lamports,
-                mint_space as u64,
-                &spl_token_2022::id(),
-            ),
-            &[
-                ctx.accounts.payer.to_account_info(),
-                ctx.accounts.mint.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
+        // Create the mint account, signing for it ourselves when it is a PDA
+        let mint_bump_seed = mint_bump.map(|bump| [bump]);
+        let mint_signer_seeds: Vec<&[u8]> = match (&args.mint_seed, &mint_bump_seed) {
+            (Some(seed), Some(bump)) => vec![MINT_SEED, creator_key.as_ref(), seed, bump],
+            _ => vec![],
+        };
+        create_account_with_seeds(
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.mint.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            mint_space,
+            lamports,
+            &token_program_id,
+            &mint_signer_seeds,
         )?;
 
-        // Initialize the mint account
+        if is_token_2022 {
+            // Point the metadata at the mint itself
+            anchor_lang::solana_program::program::invoke(
+                &metadata_pointer::instruction::initialize(
+                    &token_program_id,
+                    &mint_key,
+                    Some(authority_key),
+                    Some(mint_key),
+                )?,
+                &[
+                    ctx.accounts.mint.to_account_info(),
+                    ctx.accounts.token_program.to_account_info(),
+                ],
+            )?;
+
+            // Extensions must be initialized before the mint itself
+            for ix in extensions.init_instructions(&mint_key, &authority_key)? {
+                anchor_lang::solana_program::program::invoke(
+                    &ix,
+                    &[
+                        ctx.accounts.mint.to_account_info(),
+                        ctx.accounts.token_program.to_account_info(),
+                    ],
+                )?;
+            }
+        }
+
+        // The payer holds both authorities while the supply is minted and hands
+        // them to their final owners once setup is done
         anchor_lang::solana_program::program::invoke(
             &initialize_mint2(
-                &spl_token_2022::id(),
-                &ctx.accounts.mint.key(),
-                &ctx.accounts.payer.key(),
-                Some(&ctx.accounts.payer.key()),
+                &token_program_id,
+                &mint_key,
+                &payer_key,
+                Some(&payer_key),
                 args.decimals,
             )?,
             &[
@@ -79,37 +296,266 @@ pub mod mintcraft {
             ],
         )?;
 
-        // Create the payer's associated token account
-        anchor_spl::associated_token::create(CpiContext::new(
-            ctx.accounts.associated_token_program.to_account_info(),
-            anchor_spl::associated_token::Create {
-                payer: ctx.accounts.payer.to_account_info(),
-                associated_token: ctx.accounts.associated_token_account.to_account_info(),
-                authority: ctx.accounts.payer.to_account_info(),
-                mint: ctx.accounts.mint.to_account_info(),
-                system_program: ctx.accounts.system_program.to_account_info(),
-                token_program: ctx.accounts.token_program.to_account_info(),
-            },
-        ))?;
-
-        // Mint initial supply to the associated token account
-        anchor_lang::solana_program::program::invoke(
+        if is_token_2022 {
+            // Write name/symbol/uri into the mint's TokenMetadata extension
+            for ix in token_metadata_instructions(&token_program_id, &payer_key, metadata) {
+                anchor_lang::solana_program::program::invoke(
+                    &ix,
+                    &[
+                        ctx.accounts.mint.to_account_info(),
+                        ctx.accounts.payer.to_account_info(),
+                        ctx.accounts.token_program.to_account_info(),
+                    ],
+                )?;
+            }
+        }
+
+        let mut thawed: Vec<Pubkey> = Vec::with_capacity(distribution.len());
+        for (owner, token_account, amount) in distribution {
+            // Idempotent so repeated owners in the allocation list share one account
+            anchor_spl::associated_token::create_idempotent(CpiContext::new(
+                ctx.accounts.associated_token_program.to_account_info(),
+                anchor_spl::associated_token::Create {
+                    payer: ctx.accounts.payer.to_account_info(),
+                    associated_token: token_account.clone(),
+                    authority: owner,
+                    mint: ctx.accounts.mint.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                },
+            ))?;
+
+            // New accounts start frozen under DefaultAccountState::Frozen, so thaw ours
+            // once; thawing a repeated owner's account again fails with InvalidState
+            if extensions.default_account_state == Some(DefaultAccountStateArg::Frozen)
+                && !thawed.contains(token_account.key)
+            {
+                thawed.push(token_account.key());
+                anchor_lang::solana_program::program::invoke(
+                    &thaw_account(
+                        &token_program_id,
+                        token_account.key,
+                        &mint_key,
+                        &payer_key,
+                        &[],
+                    )?,
+                    &[
+                        token_account.clone(),
+                        ctx.accounts.mint.to_account_info(),
+                        ctx.accounts.payer.to_account_info(),
+                        ctx.accounts.token_program.to_account_info(),
+                    ],
+                )?;
+            }
+
+            if amount == 0 {
+                continue;
+            }
+            anchor_lang::solana_program::program::invoke(
+                &mint_to(
+                    &token_program_id,
+                    &mint_key,
+                    token_account.key,
+                    &payer_key,
+                    &[],
+                    amount,
+                )?,
+                &[
+                    ctx.accounts.mint.to_account_info(),
+                    token_account,
+                    ctx.accounts.payer.to_account_info(),
+                    ctx.accounts.token_program.to_account_info(),
+                ],
+            )?;
+        }
+
+        // Hook-protected mints get their max-wallet config and meta list atomically
+        if let Some(hook) = &extensions.transfer_hook {
+            let (Some(config), Some(extra_account_metas)) = (
+                &ctx.accounts.max_wallet_config,
+                &ctx.accounts.extra_account_metas,
+            ) else {
+                return err!(CreateTokenError::MissingTransferHookAccounts);
+            };
+            let mut config_state = MaxWalletConfig {
+                authority: authority_key,
+                max_wallet_bps: hook.max_wallet_bps,
+                max_tx_bps: hook.max_tx_bps,
+                version: MAX_WALLET_CONFIG_VERSION,
+                ..Default::default()
+            };
+            config_state.sync_policies();
+            let policies = config_state.policies;
+            create_max_wallet_config(
+                &ctx.accounts.payer.to_account_info(),
+                &config.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &mint_key,
+                ctx.program_id,
+                config_state,
+            )?;
+            write_extra_account_metas(
+                &ctx.accounts.payer.to_account_info(),
+                &extra_account_metas.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &mint_key,
+                ctx.program_id,
+                policies,
+                &[],
+            )?;
+            emit!(MaxWalletConfigInitialized {
+                mint: mint_key,
+                authority: authority_key,
+                max_wallet_bps: hook.max_wallet_bps,
+                policies,
+            });
+        }
+
+        // Controlled mints hand the mint authority to a PDA enforcing the cap
+        if let (Some(controlled), Some((mint_authority_pda, mint_authority_bump))) =
+            (&args.controlled_minting, mint_authority_pda)
+        {
+            let Some(config) = &ctx.accounts.mint_authority_config else {
+                return err!(CreateTokenError::MissingMintAuthorityConfig);
+            };
+            require_keys_eq!(
+                config.key(),
+                mint_authority_pda,
+                MintControlError::InvalidMintAuthorityConfig
+            );
+            let rent = Rent::get()?;
+            create_account_with_seeds(
+                &ctx.accounts.payer.to_account_info(),
+                &config.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                MintAuthorityConfig::LEN,
+                rent.minimum_balance(MintAuthorityConfig::LEN),
+                ctx.program_id,
+                &[MINT_AUTHORITY_SEED, mint_key.as_ref(), &[mint_authority_bump]],
+            )?;
+            let state = MintAuthorityConfig {
+                authority: authority_key,
+                mint: mint_key,
+                max_supply: controlled.max_supply,
+                epoch_emission_limit: controlled.epoch_emission_limit,
+                current_epoch: Clock::get()?.epoch,
+                minted_this_epoch: 0,
+                bump: mint_authority_bump,
+            };
+            let config_info = config.to_account_info();
+            let mut data = config_info.try_borrow_mut_data()?;
+            state.try_serialize(&mut &mut data[..])?;
+        }
+
+        if final_mint_authority != Some(payer_key) {
+            set_mint_authority(
+                &ctx.accounts.mint.to_account_info(),
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                AuthorityType::MintTokens,
+                final_mint_authority.as_ref(),
+            )?;
+        }
+        if final_freeze_authority != Some(payer_key) {
+            set_mint_authority(
+                &ctx.accounts.mint.to_account_info(),
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                AuthorityType::FreezeAccount,
+                final_freeze_authority.as_ref(),
+            )?;
+        }
+
+        if args.revoke_mint_authority || args.revoke_freeze_authority {
+            emit!(AuthoritiesRevoked {
+                mint: mint_key,
+                supply: args.supply,
+                mint_authority_revoked: args.revoke_mint_authority,
+                freeze_authority_revoked: args.revoke_freeze_authority,
+            });
+        }
+
+        emit!(TokenCreated {
+            mint: mint_key,
+            creator: payer_key,
+            token_program: token_program_id,
+            name: args.name,
+            symbol: args.symbol,
+            uri: args.uri,
+            decimals: args.decimals,
+            supply: args.supply,
+        });
+
+        Ok(())
+    }
+
+    /// Mint more supply through the program-controlled mint authority PDA,
+    /// within the hard cap and the per-epoch emission limit
+    pub fn mint_additional(ctx: Context<MintAdditional>, amount: u64) -> Result<()> {
+        let config = &mut ctx.accounts.mint_authority_config;
+
+        let current_supply = {
+            let mint_data = ctx
+                .accounts
+                .mint
+                .try_borrow_data()
+                .map_err(|_| MintControlError::InvalidMint)?;
+            StateWithExtensions::<SplMint>::unpack(&mint_data)
+                .map_err(|_| MintControlError::InvalidMint)?
+                .base
+                .supply
+        };
+        let new_supply = current_supply
+            .checked_add(amount)
+            .ok_or(MintControlError::NumericalOverflow)?;
+        require!(
+            new_supply <= config.max_supply,
+            MintControlError::SupplyCapExceeded
+        );
+
+        let epoch = Clock::get()?.epoch;
+        if epoch != config.current_epoch {
+            config.current_epoch = epoch;
+            config.minted_this_epoch = 0;
+        }
+        let minted_this_epoch = config
+            .minted_this_epoch
+            .checked_add(amount)
+            .ok_or(MintControlError::NumericalOverflow)?;
+        require!(
+            config.epoch_emission_limit == 0 || minted_this_epoch <= config.epoch_emission_limit,
+            MintControlError::EpochEmissionExceeded
+        );
+        config.minted_this_epoch = minted_this_epoch;
+
+        let mint_key = ctx.accounts.mint.key();
+        invoke_signed(
             &mint_to(
-                &spl_token_2022::id(),
-                &ctx.accounts.mint.key(),
-                &ctx.accounts.associated_token_account.key(),
-                &ctx.accounts.payer.key(),
+                ctx.accounts.token_program.key,
+                &mint_key,
+                &ctx.accounts.destination.key(),
+                &config.key(),
                 &[],
-                args.supply,
+                amount,
             )?,
             &[
                 ctx.accounts.mint.to_account_info(),
-                ctx.accounts.associated_token_account.to_account_info(),
-                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.destination.to_account_info(),
+                config.to_account_info(),
                 ctx.accounts.token_program.to_account_info(),
             ],
+            &[&[MINT_AUTHORITY_SEED, mint_key.as_ref(), &[config.bump]]],
         )?;
 
+        emit!(AdditionalMinted {
+            mint: mint_key,
+            destination: ctx.accounts.destination.key(),
+            amount,
+            supply: new_supply,
+            epoch,
+            minted_this_epoch,
+        });
+
         Ok(())
     }
 
@@ -123,92 +569,486 @@ pub mod mintcraft {
         );
 
         let config = &mut ctx.accounts.config;
-        config.authority = ctx.accounts.authority.key();
-        config.max_wallet_bps = max_wallet_bps;
-        config.bump = ctx.bumps.config;
-        config.reserved = [0; 5];
+        config.set_inner(MaxWalletConfig {
+            authority: ctx.accounts.authority.key(),
+            max_wallet_bps,
+            bump: ctx.bumps.config,
+            version: MAX_WALLET_CONFIG_VERSION,
+            ..Default::default()
+        });
+        config.sync_policies();
+
+        write_extra_account_metas(
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.extra_account_metas.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.mint.key(),
+            ctx.program_id,
+            config.policies,
+            &[],
+        )?;
 
-        let mint_key = ctx.accounts.mint.key();
-        let (extra_meta_address, extra_meta_bump) =
-            spl_transfer_hook_interface::get_extra_account_metas_address_and_bump_seed(
-                &mint_key,
+        emit!(MaxWalletConfigInitialized {
+            mint: ctx.accounts.mint.key(),
+            authority: config.authority,
+            max_wallet_bps,
+            policies: config.policies,
+        });
+
+        Ok(())
+    }
+
+    pub fn update_max_wallet_config(
+        ctx: Context<UpdateMaxWalletConfig>,
+        update: MaxWalletConfigUpdate,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        if let Some(bps) = update.max_wallet_bps {
+            require!(bps <= MAX_BPS, MaxWalletError::InvalidMaxWalletBps);
+            config.max_wallet_bps = bps;
+        }
+
+        if let Some(bps) = update.max_tx_bps {
+            require!(bps <= MAX_BPS, MaxWalletError::InvalidMaxTxBps);
+            config.max_tx_bps = bps;
+        }
+
+        if let Some(policy) = update.transfer_policy {
+            config.transfer_policy = policy;
+        }
+
+        if let Some(timestamp) = update.trading_enabled_at {
+            config.trading_enabled_at = timestamp;
+        }
+
+        if let Some(secs) = update.launch_window_secs {
+            config.launch_window_secs = secs;
+        }
+
+        if let Some(bps) = update.launch_max_wallet_bps {
+            require!(bps <= MAX_BPS, MaxWalletError::InvalidMaxWalletBps);
+            config.launch_max_wallet_bps = bps;
+        }
+
+        if let Some(bps) = update.launch_max_tx_bps {
+            require!(bps <= MAX_BPS, MaxWalletError::InvalidMaxTxBps);
+            config.launch_max_tx_bps = bps;
+        }
+
+        if let Some(secs) = update.cooldown_secs {
+            config.cooldown_secs = secs;
+        }
+
+        if let Some(bps) = update.max_volume_bps {
+            require!(bps <= MAX_BPS, MaxWalletError::InvalidMaxVolumeBps);
+            config.max_volume_bps = bps;
+        }
+
+        if let Some(secs) = update.volume_window_secs {
+            config.volume_window_secs = secs;
+        }
+
+        if let Some(guardian) = update.guardian {
+            config.guardian = guardian;
+        }
+
+        if let Some(bps) = update.buy_max_tx_bps {
+            require!(bps <= MAX_BPS, MaxWalletError::InvalidMaxTxBps);
+            config.buy_max_tx_bps = bps;
+        }
+
+        if let Some(bps) = update.sell_max_tx_bps {
+            require!(bps <= MAX_BPS, MaxWalletError::InvalidMaxTxBps);
+            config.sell_max_tx_bps = bps;
+        }
+
+        if let Some(bps) = update.buy_max_wallet_bps {
+            require!(bps <= MAX_BPS, MaxWalletError::InvalidMaxWalletBps);
+            config.buy_max_wallet_bps = bps;
+        }
+
+        if let Some(enabled) = update.stats_enabled {
+            config.stats_enabled = enabled;
+        }
+
+        if let Some(amount) = update.max_tx_amount {
+            config.max_tx_amount = amount;
+        }
+
+        // Launch values may only tighten the normal limits
+        require!(
+            config.launch_max_wallet_bps == 0
+                || config.max_wallet_bps == 0
+                || config.launch_max_wallet_bps <= config.max_wallet_bps,
+            MaxWalletError::InvalidMaxWalletBps
+        );
+        require!(
+            config.launch_max_tx_bps == 0
+                || config.max_tx_bps == 0
+                || config.launch_max_tx_bps <= config.max_tx_bps,
+            MaxWalletError::InvalidMaxTxBps
+        );
+
+        // Enabling or disabling a rule changes the accounts `execute` needs
+        if config.sync_policies() {
+            rewrite_extra_account_metas(
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.extra_account_metas.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                config,
+                &ctx.accounts.mint.key(),
                 ctx.program_id,
+            )?;
+        }
+
+        emit!(MaxWalletConfigUpdated {
+            mint: ctx.accounts.mint.key(),
+            authority: config.authority,
+            update,
+            policies: config.policies,
+        });
+
+        Ok(())
+    }
+
+    /// Realloc a config written by an older program version to the current
+    /// layout. Settings are kept; fields the old layout lacked are zero, which
+    /// every setting reads as disabled or default. Anyone can pay for it, since
+    /// `execute` can't read the old layout and transfers stop until it runs.
+    pub fn migrate_max_wallet_config(ctx: Context<MigrateMaxWalletConfig>) -> Result<()> {
+        let config_info = ctx.accounts.config.to_account_info();
+        let mut config = {
+            let data = config_info.try_borrow_data()?;
+            require!(
+                data.len() <= MaxWalletConfig::LEN
+                    && data.starts_with(MaxWalletConfig::DISCRIMINATOR),
+                MaxWalletError::InvalidMaxWalletConfigAccount
             );
-        require_keys_eq!(
-            extra_meta_address,
-            ctx.accounts.extra_account_metas.key(),
-            MaxWalletError::InvalidExtraAccountMetaAccount
+            let mut padded = data.to_vec();
+            padded.resize(MaxWalletConfig::LEN, 0);
+            MaxWalletConfig::try_deserialize(&mut &padded[..])?
+        };
+        require!(
+            config.version < MAX_WALLET_CONFIG_VERSION,
+            MaxWalletError::ConfigAlreadyMigrated
         );
 
-        let extra_meta_info = ctx.accounts.extra_account_metas.to_account_info();
-        let extra_meta_space = ExtraAccountMetaList::size_of(1)
-            .map_err(|_| MaxWalletError::ExtraAccountMetaSerialization)?;
-        let rent = Rent::get()?;
-        let required_lamports = rent.minimum_balance(extra_meta_space);
-
-        if extra_meta_info.owner != ctx.program_id || extra_meta_info.data_len() == 0 {
-            invoke_signed(
-                &system_instruction::create_account(
-                    &ctx.accounts.payer.key(),
-                    &extra_meta_address,
-                    required_lamports,
-                    extra_meta_space as u64,
-                    ctx.program_id,
-                ),
+        let rent_due = Rent::get()?
+            .minimum_balance(MaxWalletConfig::LEN)
+            .saturating_sub(config_info.lamports());
+        if rent_due > 0 {
+            anchor_lang::solana_program::program::invoke(
+                &system_instruction::transfer(&ctx.accounts.payer.key(), config_info.key, rent_due),
                 &[
                     ctx.accounts.payer.to_account_info(),
-                    extra_meta_info.clone(),
+                    config_info.clone(),
                     ctx.accounts.system_program.to_account_info(),
                 ],
-                &[&collect_extra_account_metas_signer_seeds(
-                    &mint_key,
-                    &[extra_meta_bump],
-                )],
             )?;
         }
+        config_info.resize(MaxWalletConfig::LEN)?;
+
+        config.version = MAX_WALLET_CONFIG_VERSION;
+        config.sync_policies();
+        config.try_serialize(&mut &mut config_info.try_borrow_mut_data()?[..])?;
+
+        // Older meta lists predate some policy accounts
+        rewrite_extra_account_metas(
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.extra_account_metas.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &config,
+            &ctx.accounts.mint.key(),
+            ctx.program_id,
+        )?;
 
-        let extra_meta = ExtraAccountMeta::new_with_seeds(
-            &[
-                Seed::Literal {
-                    bytes: MAX_WALLET_CONFIG_SEED.to_vec(),
-                },
-                Seed::AccountKey { index: 1 }, // mint account
-            ],
-            false,
-            false,
-        )
-        .map_err(|_| MaxWalletError::ExtraAccountMetaSerialization)?;
+        emit!(MaxWalletConfigMigrated {
+            mint: ctx.accounts.mint.key(),
+            version: config.version,
+            policies: config.policies,
+        });
 
-        {
-            let mut data = extra_meta_info
-                .try_borrow_mut_data()
-                .map_err(|_| MaxWalletError::ExtraAccountMetaSerialization)?;
-            let result = if data.iter().all(|byte| *byte == 0) {
-                ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &[extra_meta])
-            } else {
-                ExtraAccountMetaList::update::<ExecuteInstruction>(&mut data, &[extra_meta])
-            };
-            result.map_err(|_| MaxWalletError::ExtraAccountMetaSerialization)?;
+        Ok(())
+    }
+
+    /// Reject every non-exempt transfer of the mint (authority or guardian)
+    pub fn pause(ctx: Context<SetPaused>) -> Result<()> {
+        set_paused(ctx, true)
+    }
+
+    /// Lift a pause (authority or guardian)
+    pub fn unpause(ctx: Context<SetPaused>) -> Result<()> {
+        set_paused(ctx, false)
+    }
+
+    /// Rewrite the mint's extra account meta list, e.g. after an upgrade adds metas
+    pub fn update_extra_account_metas(ctx: Context<UpdateExtraAccountMetas>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.sync_policies();
+        rewrite_extra_account_metas(
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.extra_account_metas.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            config,
+            &ctx.accounts.mint.key(),
+            ctx.program_id,
+        )?;
+
+        emit!(ExtraAccountMetasUpdated {
+            mint: ctx.accounts.mint.key(),
+            policies: config.policies,
+        });
+        Ok(())
+    }
+
+    /// Forward `execute` to another transfer-hook program after mintcraft's own
+    /// checks, or stop forwarding with `None` (authority only). Re-run after the
+    /// downstream program changes its extra account metas.
+    pub fn set_downstream_hook(
+        ctx: Context<SetDownstreamHook>,
+        program: Option<Pubkey>,
+    ) -> Result<()> {
+        let mint_key = ctx.accounts.mint.key();
+        let segment = match program {
+            Some(program) => {
+                require!(
+                    program != crate::ID && program != Pubkey::default(),
+                    MaxWalletError::InvalidDownstreamHook
+                );
+                let Some(validation) = &ctx.accounts.downstream_extra_account_metas else {
+                    return err!(MaxWalletError::MissingDownstreamAccounts);
+                };
+                require_keys_eq!(
+                    validation.key(),
+                    spl_transfer_hook_interface::get_extra_account_metas_address(
+                        &mint_key, &program
+                    ),
+                    MaxWalletError::InvalidDownstreamExtraAccountMetas
+                );
+                let downstream_metas =
+                    downstream::read_extra_account_metas(&validation.to_account_info())?;
+                downstream::segment(&program, &validation.key(), &downstream_metas)?
+            }
+            None => Vec::new(),
+        };
+
+        let config = &mut ctx.accounts.config;
+        config.downstream_hook = program.unwrap_or_default();
+        config.downstream_meta_count = u8::try_from(segment.len().saturating_sub(2))
+            .map_err(|_| MaxWalletError::InvalidDownstreamExtraAccountMetas)?;
+        config.sync_policies();
+
+        write_extra_account_metas(
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.extra_account_metas.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &mint_key,
+            ctx.program_id,
+            config.policies,
+            &segment,
+        )?;
+
+        emit!(DownstreamHookSet {
+            mint: mint_key,
+            program: config.downstream_hook,
+            meta_count: config.downstream_meta_count,
+        });
+        Ok(())
+    }
+
+    /// Exempt a wallet owner from the max-wallet cap (authority only)
+    pub fn add_max_wallet_exemption(
+        ctx: Context<AddMaxWalletExemption>,
+        owner: Pubkey,
+    ) -> Result<()> {
+        let exemption = &mut ctx.accounts.exemption;
+        exemption.mint = ctx.accounts.mint.key();
+        exemption.owner = owner;
+        exemption.bump = ctx.bumps.exemption;
+
+        emit!(MaxWalletExemptionChanged {
+            mint: exemption.mint,
+            owner,
+            exempt: true,
+        });
+        Ok(())
+    }
+
+    /// Remove a wallet owner's max-wallet exemption (authority only)
+    pub fn remove_max_wallet_exemption(
+        ctx: Context<RemoveMaxWalletExemption>,
+        owner: Pubkey,
+    ) -> Result<()> {
+        emit!(MaxWalletExemptionChanged {
+            mint: ctx.accounts.mint.key(),
+            owner,
+            exempt: false,
+        });
+        Ok(())
+    }
+
+    /// Put a wallet owner on the mint's blocklist/allowlist (authority only)
+    pub fn add_transfer_list_entry(
+        ctx: Context<AddTransferListEntry>,
+        owner: Pubkey,
+    ) -> Result<()> {
+        let entry = &mut ctx.accounts.entry;
+        entry.mint = ctx.accounts.mint.key();
+        entry.owner = owner;
+        entry.bump = ctx.bumps.entry;
+
+        emit!(TransferListEntryChanged {
+            mint: entry.mint,
+            owner,
+            listed: true,
+        });
+        Ok(())
+    }
+
+    /// Take a wallet owner off the mint's blocklist/allowlist (authority only)
+    pub fn remove_transfer_list_entry(
+        ctx: Context<RemoveTransferListEntry>,
+        owner: Pubkey,
+    ) -> Result<()> {
+        emit!(TransferListEntryChanged {
+            mint: ctx.accounts.mint.key(),
+            owner,
+            listed: false,
+        });
+        Ok(())
+    }
+
+    /// Create the per-wallet state used for cooldowns and volume limits (anyone can pay)
+    pub fn initialize_wallet_state(
+        ctx: Context<InitializeWalletState>,
+        owner: Pubkey,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.wallet_state;
+        state.mint = ctx.accounts.mint.key();
+        state.owner = owner;
+        state.last_transfer_at = 0;
+        state.bump = ctx.bumps.wallet_state;
+        state.window_start = 0;
+        state.window_volume = 0;
+        state.previous_window_volume = 0;
+
+        emit!(WalletStateInitialized {
+            mint: state.mint,
+            owner,
+        });
+        Ok(())
+    }
+
+    /// Register an AMM pool token account so transfers out of it count as buys
+    /// and transfers into it as sells (authority only)
+    pub fn add_pool_account(ctx: Context<AddPoolAccount>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        pool.mint = ctx.accounts.mint.key();
+        pool.token_account = ctx.accounts.pool_token_account.key();
+        pool.bump = ctx.bumps.pool;
+
+        let config = &mut ctx.accounts.config;
+        config.pool_count = config
+            .pool_count
+            .checked_add(1)
+            .ok_or(MaxWalletError::NumericalOverflow)?;
+        if config.sync_policies() {
+            rewrite_extra_account_metas(
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.extra_account_metas.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                config,
+                &ctx.accounts.mint.key(),
+                ctx.program_id,
+            )?;
         }
 
+        emit!(PoolAccountChanged {
+            mint: pool.mint,
+            token_account: pool.token_account,
+            registered: true,
+            pool_count: config.pool_count,
+        });
         Ok(())
     }
 
-    pub fn update_max_wallet_config(
-        ctx: Context<UpdateMaxWalletConfig>,
-        max_wallet_bps: u16,
+    /// Unregister a pool token account (authority only)
+    pub fn remove_pool_account(
+        ctx: Context<RemovePoolAccount>,
+        token_account: Pubkey,
     ) -> Result<()> {
-        require!(
-            max_wallet_bps <= MAX_BPS,
-            MaxWalletError::InvalidMaxWalletBps
-        );
         let config = &mut ctx.accounts.config;
-        config.max_wallet_bps = max_wallet_bps;
+        config.pool_count = config.pool_count.saturating_sub(1);
+        if config.sync_policies() {
+            rewrite_extra_account_metas(
+                &ctx.accounts.authority.to_account_info(),
+                &ctx.accounts.extra_account_metas.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                config,
+                &ctx.accounts.mint.key(),
+                ctx.program_id,
+            )?;
+        }
+
+        emit!(PoolAccountChanged {
+            mint: ctx.accounts.mint.key(),
+            token_account,
+            registered: false,
+            pool_count: config.pool_count,
+        });
+        Ok(())
+    }
+
+    /// Create the per-mint counters and have the hook update them (authority only)
+    pub fn initialize_hook_stats(ctx: Context<InitializeHookStats>) -> Result<()> {
+        let stats = &mut ctx.accounts.stats;
+        stats.mint = ctx.accounts.mint.key();
+        stats.bump = ctx.bumps.stats;
+        stats.buys = 0;
+        stats.sells = 0;
+        stats.wallet_transfers = 0;
+        stats.buy_volume = 0;
+        stats.sell_volume = 0;
+        stats.transfers = 0;
+        stats.volume = 0;
+        stats.largest_transfer = 0;
+        stats.last_slot = 0;
+        stats.rule_passes = [0; 8];
+
+        let config = &mut ctx.accounts.config;
+        config.stats_enabled = true;
+        if config.sync_policies() {
+            rewrite_extra_account_metas(
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.extra_account_metas.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                config,
+                &ctx.accounts.mint.key(),
+                ctx.program_id,
+            )?;
+        }
+
+        emit!(HookStatsInitialized {
+            mint: stats.mint,
+            policies: config.policies,
+        });
         Ok(())
     }
 
+    /// Transfer-hook entrypoint, invoked by Token-2022 on every transfer.
+    ///
+    /// Cost scales with the enabled policies: every call unpacks the mint and
+    /// both token accounts; stats add a `HookStats` write, cooldown and volume
+    /// a `WalletState` write each, and a downstream hook adds a CPI. The
+    /// budgets per policy are in `tests/compute_budget.rs`. Build with
+    /// `no-log-messages` to drop the per-transfer logs, including Anchor's
+    /// instruction-name line.
     #[instruction(discriminator = &EXECUTE_DISCRIMINATOR)]
-    pub fn execute(ctx: Context<TransferHookExecute>, amount: u64) -> Result<()> {
+    pub fn execute<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferHookExecute<'info>>,
+        amount: u64,
+    ) -> Result<()> {
         process_execute(ctx, amount)
     }
 
@@ -232,10 +1072,18 @@ pub mod mintcraft {
         config.total_distributed = 0;
         config.bump = ctx.bumps.config;
 
-        msg!("Reflection config initialized");
-        msg!("Reward token mint: {}", reward_token_mint);
-        msg!("Min holding: {}", min_holding);
-        msg!("Gas rebate BPS: {}", gas_rebate_bps);
+        log!("Reflection config initialized");
+        log!("Reward token mint: {}", reward_token_mint);
+        log!("Min holding: {}", min_holding);
+        log!("Gas rebate BPS: {}", gas_rebate_bps);
+
+        emit!(ReflectionConfigInitialized {
+            mint: ctx.accounts.mint.key(),
+            authority: config.authority,
+            reward_token_mint,
+            min_holding,
+            gas_rebate_bps,
+        });
 
         Ok(())
     }
@@ -250,15 +1098,22 @@ pub mod mintcraft {
 
         if let Some(min) = min_holding {
             config.min_holding = min;
-            msg!("Updated min holding: {}", min);
+            log!("Updated min holding: {}", min);
         }
 
         if let Some(bps) = gas_rebate_bps {
             require!(bps <= MAX_BPS, ReflectionError::InvalidGasRebateBps);
             config.gas_rebate_bps = bps;
-            msg!("Updated gas rebate BPS: {}", bps);
+            log!("Updated gas rebate BPS: {}", bps);
         }
 
+        emit!(ReflectionConfigUpdated {
+            mint: ctx.accounts.mint.key(),
+            authority: config.authority,
+            min_holding: config.min_holding,
+            gas_rebate_bps: config.gas_rebate_bps,
+        });
+
         Ok(())
     }
 
@@ -289,28 +1144,34 @@ pub mod mintcraft {
             .checked_sub(gas_rebate)
             .ok_or(ReflectionError::InsufficientAmount)?;
 
-        msg!("Claiming reflection");
-        msg!("Gross amount: {}", amount);
-        msg!("Gas rebate ({}%): {}", config.gas_rebate_bps as f64 / 100.0, gas_rebate);
-        msg!("Net amount: {}", net_amount);
+        log!("Claiming reflection");
+        log!("Gross amount: {}", amount);
+        log!("Gas rebate ({} bps): {}", config.gas_rebate_bps, gas_rebate);
+        log!("Net amount: {}", net_amount);
 
         // Transfer net amount to user
-        let cpi_accounts = anchor_spl::token_2022::Transfer {
+        let cpi_accounts = anchor_spl::token_interface::TransferChecked {
             from: ctx.accounts.treasury_token_account.to_account_info(),
+            mint: ctx.accounts.reward_mint.to_account_info(),
             to: ctx.accounts.user_token_account.to_account_info(),
             authority: ctx.accounts.treasury_authority.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
 
-        anchor_spl::token_2022::transfer(cpi_ctx, net_amount)?;
+        anchor_spl::token_interface::transfer_checked(
+            cpi_ctx,
+            net_amount,
+            ctx.accounts.reward_mint.decimals,
+        )?;
 
         // Update user claim state
         user_state.total_claimed = user_state
             .total_claimed
             .checked_add(amount)
             .ok_or(ReflectionError::NumericalOverflow)?;
-        user_state.last_claim_timestamp = Clock::get()?.unix_timestamp;
+        let timestamp = Clock::get()?.unix_timestamp;
+        user_state.last_claim_timestamp = timestamp;
 
         // Update global config
         let config_mut = &mut ctx.accounts.reflection_config;
@@ -319,6 +1180,15 @@ pub mod mintcraft {
             .checked_add(amount)
             .ok_or(ReflectionError::NumericalOverflow)?;
 
+        emit!(ReflectionClaimed {
+            mint: ctx.accounts.mint.key(),
+            user: ctx.accounts.user.key(),
+            gross_amount: amount,
+            gas_rebate,
+            net_amount,
+            timestamp,
+        });
+
         Ok(())
     }
 }
@@ -337,13 +1207,25 @@ pub struct CreateToken<'info> {
     /// CHECK: Mint account created within the instruction
     #[account(mut)]
     pub mint: UncheckedAccount<'info>,
-    /// CHECK: Associated token account created within the instruction
+    /// CHECK: Owner of the initial supply when no allocations are given; only
+    /// used to derive its token account
+    pub recipient: UncheckedAccount<'info>,
+    /// CHECK: Recipient's associated token account, created within the instruction
     #[account(mut)]
     pub associated_token_account: AccountInfo<'info>,
-    pub token_program: Program<'info, Token2022>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub rent: Sysvar<'info, Rent>,
+    /// CHECK: MaxWalletConfig PDA, created when the transfer hook is requested
+    #[account(mut)]
+    pub max_wallet_config: Option<UncheckedAccount<'info>>,
+    /// CHECK: Extra account meta PDA, created when the transfer hook is requested
+    #[account(mut)]
+    pub extra_account_metas: Option<UncheckedAccount<'info>>,
+    /// CHECK: MintAuthorityConfig PDA, created when controlled minting is requested
+    #[account(mut)]
+    pub mint_authority_config: Option<UncheckedAccount<'info>>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -353,32 +1235,694 @@ pub struct TokenArgs {
     pub decimals: u8,
     pub supply: u64,
     pub uri: String,
+    pub extensions: Option<MintExtensionArgs>,
+    /// Final mint authority; may be a multisig or PDA that cannot sign here.
+    pub mint_authority: Pubkey,
+    /// Final freeze authority, or `None` for a mint that can never freeze.
+    pub freeze_authority: Option<Pubkey>,
+    /// Drop the mint authority after the initial supply, fixing the supply forever.
+    pub revoke_mint_authority: bool,
+    /// Drop the freeze authority after setup, regardless of `freeze_authority`.
+    pub revoke_freeze_authority: bool,
+    /// Genesis split of `supply`; when empty everything goes to `recipient`.
+    pub allocations: Vec<Allocation>,
+    /// Hand the mint authority to a program PDA; `mint_authority` then only
+    /// controls `mint_additional`.
+    pub controlled_minting: Option<ControlledMintArgs>,
+    /// Derive the mint as a PDA of (`"mint"`, payer, seed) instead of using a
+    /// fresh keypair, making the address predictable before the transaction.
+    pub mint_seed: Option<Vec<u8>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ControlledMintArgs {
+    pub max_supply: u64,
+    /// Maximum amount mintable per Solana epoch, 0 for no epoch limit.
+    pub epoch_emission_limit: u64,
+}
+
+impl TokenArgs {
+    fn validate(&self) -> Result<()> {
+        require!(!self.name.is_empty(), CreateTokenError::NameEmpty);
+        require!(
+            self.name.len() <= MAX_NAME_LENGTH,
+            CreateTokenError::NameTooLong
+        );
+        require!(!self.symbol.is_empty(), CreateTokenError::SymbolEmpty);
+        require!(
+            self.symbol.len() <= MAX_SYMBOL_LENGTH,
+            CreateTokenError::SymbolTooLong
+        );
+        require!(
+            self.symbol.bytes().all(|byte| byte.is_ascii_alphanumeric()),
+            CreateTokenError::InvalidSymbolCharacters
+        );
+        require!(
+            self.uri.len() <= MAX_URI_LENGTH,
+            CreateTokenError::UriTooLong
+        );
+        // An empty URI is allowed for tokens without off-chain metadata
+        require!(
+            self.uri.is_empty()
+                || ALLOWED_URI_SCHEMES
+                    .iter()
+                    .any(|scheme| self.uri.starts_with(scheme)),
+            CreateTokenError::InvalidUriScheme
+        );
+
+        require!(
+            self.decimals <= MAX_DECIMALS,
+            CreateTokenError::InvalidDecimals
+        );
+        // Only controlled mints may start empty and grow through `mint_additional`
+        require!(
+            self.supply > 0 || self.controlled_minting.is_some(),
+            CreateTokenError::ZeroSupply
+        );
+        // Less than one whole token almost always means whole tokens were passed
+        // where base units were expected, or vice versa
+        let one_token = 10u64
+            .checked_pow(self.decimals as u32)
+            .ok_or(CreateTokenError::SupplyOverflow)?;
+        require!(
+            self.supply == 0 || self.supply >= one_token,
+            CreateTokenError::SupplyBelowOneToken
+        );
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Allocation {
+    pub owner: Pubkey,
+    pub share: AllocationShare,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub enum AllocationShare {
+    Amount(u64),
+    Bps(u16),
+}
+
+/// Resolves each allocation to a raw amount and checks they add up to `supply`.
+/// `Bps` shares are floored and the rounding remainder goes to the last of them.
+fn allocation_amounts(allocations: &[Allocation], supply: u64) -> Result<Vec<u64>> {
+    // Exact sum in units of 1/MAX_BPS, so rounding cannot hide a short split
+    let mut scaled_total: u128 = 0;
+    let mut total: u64 = 0;
+    let mut last_bps = None;
+    let mut amounts = Vec::with_capacity(allocations.len());
+    for (index, allocation) in allocations.iter().enumerate() {
+        let amount = match allocation.share {
+            AllocationShare::Amount(amount) => {
+                scaled_total = (amount as u128)
+                    .checked_mul(MAX_BPS as u128)
+                    .and_then(|scaled| scaled_total.checked_add(scaled))
+                    .ok_or(CreateTokenError::SupplyOverflow)?;
+                amount
+            }
+            AllocationShare::Bps(bps) => {
+                require!(bps <= MAX_BPS, CreateTokenError::InvalidAllocationBps);
+                let scaled = (supply as u128)
+                    .checked_mul(bps as u128)
+                    .ok_or(CreateTokenError::SupplyOverflow)?;
+                scaled_total = scaled_total
+                    .checked_add(scaled)
+                    .ok_or(CreateTokenError::SupplyOverflow)?;
+                last_bps = Some(index);
+                (scaled / (MAX_BPS as u128)) as u64
+            }
+        };
+        total = total
+            .checked_add(amount)
+            .ok_or(CreateTokenError::SupplyOverflow)?;
+        amounts.push(amount);
+    }
+    require!(
+        scaled_total == (supply as u128) * (MAX_BPS as u128),
+        CreateTokenError::AllocationSumMismatch
+    );
+    if let Some(index) = last_bps {
+        amounts[index] += supply - total;
+    }
+    Ok(amounts)
+}
+
+/// Initializes `metadata` on its mint with `payer` as update authority, then
+/// hands that to `metadata.update_authority` when it is another key. The
+/// interface's `initialize` lists the update authority as an account, so a
+/// multisig or PDA authority that isn't passed to `create_token` would fail it.
+fn token_metadata_instructions(
+    token_program_id: &Pubkey,
+    payer: &Pubkey,
+    metadata: TokenMetadata,
+) -> Vec<Instruction> {
+    let mut instructions = vec![spl_token_metadata_interface::instruction::initialize(
+        token_program_id,
+        &metadata.mint,
+        payer,
+        &metadata.mint,
+        payer,
+        metadata.name,
+        metadata.symbol,
+        metadata.uri,
+    )];
+    if Option::<Pubkey>::from(metadata.update_authority) != Some(*payer) {
+        instructions.push(spl_token_metadata_interface::instruction::update_authority(
+            token_program_id,
+            &metadata.mint,
+            payer,
+            metadata.update_authority,
+        ));
+    }
+    instructions
+}
+
+/// Emitted when `create_token` renounces mint and/or freeze authority.
+/// With `mint_authority_revoked`, `supply` is the permanent total supply.
+#[event]
+pub struct AuthoritiesRevoked {
+    pub mint: Pubkey,
+    pub supply: u64,
+    pub mint_authority_revoked: bool,
+    pub freeze_authority_revoked: bool,
+}
+
+/// Emitted by `mint_additional`; `supply` is the mint's supply afterwards.
+#[event]
+pub struct AdditionalMinted {
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub supply: u64,
+    pub epoch: u64,
+    pub minted_this_epoch: u64,
+}
+
+/// Emitted once `create_token` has created and minted a token.
+#[event]
+pub struct TokenCreated {
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub token_program: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub decimals: u8,
+    pub supply: u64,
+}
+
+/// Optional Token-2022 mint extensions initialized by `create_token`.
+///
+/// CpiGuard is deliberately absent: it is a token account extension that
+/// holders enable themselves and cannot be turned on through CPI.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct MintExtensionArgs {
+    pub transfer_fee: Option<TransferFeeArgs>,
+    pub interest_bearing: Option<InterestBearingArgs>,
+    pub permanent_delegate: Option<Pubkey>,
+    pub non_transferable: bool,
+    pub default_account_state: Option<DefaultAccountStateArg>,
+    pub mint_close_authority: Option<Pubkey>,
+    pub transfer_hook: Option<TransferHookArgs>,
+}
+
+/// Points the mint's TransferHook at this program and sets up the max-wallet hook.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TransferHookArgs {
+    pub max_wallet_bps: u16,
+    pub max_tx_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TransferFeeArgs {
+    pub transfer_fee_bps: u16,
+    pub maximum_fee: u64,
+    pub config_authority: Option<Pubkey>,
+    pub withdraw_withheld_authority: Option<Pubkey>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InterestBearingArgs {
+    pub rate_bps: i16,
+    pub rate_authority: Option<Pubkey>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultAccountStateArg {
+    Initialized,
+    Frozen,
+}
+
+impl MintExtensionArgs {
+    fn validate(&self) -> Result<()> {
+        if let Some(fee) = &self.transfer_fee {
+            require!(
+                fee.transfer_fee_bps <= MAX_BPS,
+                CreateTokenError::InvalidTransferFeeBps
+            );
+        }
+        if let Some(hook) = &self.transfer_hook {
+            require!(
+                hook.max_wallet_bps <= MAX_BPS,
+                MaxWalletError::InvalidMaxWalletBps
+            );
+            require!(
+                hook.max_tx_bps <= MAX_BPS,
+                MaxWalletError::InvalidMaxTxBps
+            );
+        }
+        // Fees and hooks are meaningless on a token that can never move
+        require!(
+            !(self.non_transferable
+                && (self.transfer_fee.is_some() || self.transfer_hook.is_some())),
+            CreateTokenError::IncompatibleExtensions
+        );
+        Ok(())
+    }
+
+    /// Mint extensions to allocate space for, always including the metadata pointer.
+    fn extension_types(&self) -> Vec<ExtensionType> {
+        let mut types = vec![ExtensionType::MetadataPointer];
+        if self.transfer_fee.is_some() {
+            types.push(ExtensionType::TransferFeeConfig);
+        }
+        if self.interest_bearing.is_some() {
+            types.push(ExtensionType::InterestBearingConfig);
+        }
+        if self.permanent_delegate.is_some() {
+            types.push(ExtensionType::PermanentDelegate);
+        }
+        if self.non_transferable {
+            types.push(ExtensionType::NonTransferable);
+        }
+        if self.default_account_state.is_some() {
+            types.push(ExtensionType::DefaultAccountState);
+        }
+        if self.mint_close_authority.is_some() {
+            types.push(ExtensionType::MintCloseAuthority);
+        }
+        if self.transfer_hook.is_some() {
+            types.push(ExtensionType::TransferHook);
+        }
+        types
+    }
+
+    /// Extension init instructions; each only touches the mint account.
+    fn init_instructions(&self, mint: &Pubkey, authority: &Pubkey) -> Result<Vec<Instruction>> {
+        let token_program = spl_token_2022::id();
+        let mut instructions = Vec::new();
+
+        if let Some(fee) = &self.transfer_fee {
+            instructions.push(transfer_fee::instruction::initialize_transfer_fee_config(
+                &token_program,
+                mint,
+                fee.config_authority.as_ref(),
+                fee.withdraw_withheld_authority.as_ref(),
+                fee.transfer_fee_bps,
+                fee.maximum_fee,
+            )?);
+        }
+        if let Some(interest) = &self.interest_bearing {
+            instructions.push(interest_bearing_mint::instruction::initialize(
+                &token_program,
+                mint,
+                interest.rate_authority,
+                interest.rate_bps,
+            )?);
+        }
+        if let Some(delegate) = &self.permanent_delegate {
+            instructions.push(initialize_permanent_delegate(&token_program, mint, delegate)?);
+        }
+        if self.non_transferable {
+            instructions.push(initialize_non_transferable_mint(&token_program, mint)?);
+        }
+        if let Some(state) = self.default_account_state {
+            let state = match state {
+                DefaultAccountStateArg::Initialized => AccountState::Initialized,
+                DefaultAccountStateArg::Frozen => AccountState::Frozen,
+            };
+            instructions.push(
+                default_account_state::instruction::initialize_default_account_state(
+                    &token_program,
+                    mint,
+                    &state,
+                )?,
+            );
+        }
+        if let Some(close_authority) = &self.mint_close_authority {
+            instructions.push(initialize_mint_close_authority(
+                &token_program,
+                mint,
+                Some(close_authority),
+            )?);
+        }
+        if self.transfer_hook.is_some() {
+            instructions.push(transfer_hook::instruction::initialize(
+                &token_program,
+                mint,
+                Some(*authority),
+                Some(crate::ID),
+            )?);
+        }
+
+        Ok(instructions)
+    }
+}
+
+#[derive(Accounts)]
+pub struct MintAdditional<'info> {
+    pub authority: Signer<'info>,
+    /// CHECK: The Token-2022 mint controlled by `mint_authority_config`
+    #[account(mut)]
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [MINT_AUTHORITY_SEED, mint.key().as_ref()],
+        bump = mint_authority_config.bump,
+        has_one = authority,
+        has_one = mint
+    )]
+    pub mint_authority_config: Account<'info, MintAuthorityConfig>,
+    #[account(mut, token::mint = mint)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Program PDA that holds a mint's authority and the rules for new supply.
+#[account]
+pub struct MintAuthorityConfig {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub max_supply: u64,
+    pub epoch_emission_limit: u64,
+    pub current_epoch: u64,
+    pub minted_this_epoch: u64,
+    pub bump: u8,
+}
+
+impl MintAuthorityConfig {
+    pub const LEN: usize = 8 /*disc*/ + 32 + 32 + 8 + 8 + 8 + 8 + 1;
+}
+
+#[derive(Accounts)]
+pub struct InitializeMaxWalletConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub authority: Signer<'info>,
+    /// CHECK: The Token-2022 mint
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = MaxWalletConfig::LEN,
+        seeds = [MAX_WALLET_CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, MaxWalletConfig>,
+    /// CHECK: Extra account meta PDA derived by the interface
+    #[account(mut)]
+    pub extra_account_metas: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMaxWalletConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub authority: Signer<'info>,
+    /// CHECK: The Token-2022 mint
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [MAX_WALLET_CONFIG_SEED, mint.key().as_ref()],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, MaxWalletConfig>,
+    /// CHECK: Rewritten when the update enables or disables a policy
+    #[account(mut)]
+    pub extra_account_metas: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetDownstreamHook<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub authority: Signer<'info>,
+    /// CHECK: The Token-2022 mint
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [MAX_WALLET_CONFIG_SEED, mint.key().as_ref()],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, MaxWalletConfig>,
+    /// CHECK: Extra account meta PDA derived by the interface
+    #[account(mut)]
+    pub extra_account_metas: UncheckedAccount<'info>,
+    /// CHECK: The downstream program's extra account meta PDA, required when setting a program
+    pub downstream_extra_account_metas: Option<UncheckedAccount<'info>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateMaxWalletConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: The Token-2022 mint
+    pub mint: UncheckedAccount<'info>,
+    /// CHECK: Config in any older layout; validated by the handler
+    #[account(
+        mut,
+        seeds = [MAX_WALLET_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: Extra account meta PDA derived by the interface
+    #[account(mut)]
+    pub extra_account_metas: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    pub signer: Signer<'info>,
+    /// CHECK: The Token-2022 mint
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [MAX_WALLET_CONFIG_SEED, mint.key().as_ref()],
+        bump = config.bump,
+        constraint = config.can_pause(&signer.key()) @ MaxWalletError::Unauthorized
+    )]
+    pub config: Account<'info, MaxWalletConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateExtraAccountMetas<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub authority: Signer<'info>,
+    /// CHECK: The Token-2022 mint
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [MAX_WALLET_CONFIG_SEED, mint.key().as_ref()],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, MaxWalletConfig>,
+    /// CHECK: Extra account meta PDA derived by the interface
+    #[account(mut)]
+    pub extra_account_metas: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct AddMaxWalletExemption<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub authority: Signer<'info>,
+    /// CHECK: The Token-2022 mint
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        seeds = [MAX_WALLET_CONFIG_SEED, mint.key().as_ref()],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, MaxWalletConfig>,
+    #[account(
+        init,
+        payer = payer,
+        space = MaxWalletExemption::LEN,
+        seeds = [MAX_WALLET_EXEMPTION_SEED, mint.key().as_ref(), owner.as_ref()],
+        bump
+    )]
+    pub exemption: Account<'info, MaxWalletExemption>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct RemoveMaxWalletExemption<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: The Token-2022 mint
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        seeds = [MAX_WALLET_CONFIG_SEED, mint.key().as_ref()],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, MaxWalletConfig>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [MAX_WALLET_EXEMPTION_SEED, mint.key().as_ref(), owner.as_ref()],
+        bump = exemption.bump
+    )]
+    pub exemption: Account<'info, MaxWalletExemption>,
+}
+
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct AddTransferListEntry<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub authority: Signer<'info>,
+    /// CHECK: The Token-2022 mint
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        seeds = [MAX_WALLET_CONFIG_SEED, mint.key().as_ref()],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, MaxWalletConfig>,
+    #[account(
+        init,
+        payer = payer,
+        space = TransferListEntry::LEN,
+        seeds = [TRANSFER_LIST_SEED, mint.key().as_ref(), owner.as_ref()],
+        bump
+    )]
+    pub entry: Account<'info, TransferListEntry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct RemoveTransferListEntry<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: The Token-2022 mint
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        seeds = [MAX_WALLET_CONFIG_SEED, mint.key().as_ref()],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, MaxWalletConfig>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [TRANSFER_LIST_SEED, mint.key().as_ref(), owner.as_ref()],
+        bump = entry.bump
+    )]
+    pub entry: Account<'info, TransferListEntry>,
+}
+
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct InitializeWalletState<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: The Token-2022 mint
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        seeds = [MAX_WALLET_CONFIG_SEED, mint.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, MaxWalletConfig>,
+    #[account(
+        init,
+        payer = payer,
+        space = WalletState::LEN,
+        seeds = [WALLET_STATE_SEED, mint.key().as_ref(), owner.as_ref()],
+        bump
+    )]
+    pub wallet_state: Account<'info, WalletState>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddPoolAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub authority: Signer<'info>,
+    /// CHECK: The Token-2022 mint
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [MAX_WALLET_CONFIG_SEED, mint.key().as_ref()],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, MaxWalletConfig>,
+    #[account(
+        constraint = pool_token_account.mint == mint.key() @ MaxWalletError::InvalidTokenAccount
+    )]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = PoolAccount::LEN,
+        seeds = [POOL_ACCOUNT_SEED, mint.key().as_ref(), pool_token_account.key().as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, PoolAccount>,
+    /// CHECK: Extra account meta PDA derived by the interface
+    #[account(mut)]
+    pub extra_account_metas: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct InitializeMaxWalletConfig<'info> {
+#[instruction(token_account: Pubkey)]
+pub struct RemovePoolAccount<'info> {
     #[account(mut)]
-    pub payer: Signer<'info>,
     pub authority: Signer<'info>,
     /// CHECK: The Token-2022 mint
     pub mint: UncheckedAccount<'info>,
     #[account(
-        init,
-        payer = payer,
-        space = MaxWalletConfig::LEN,
+        mut,
         seeds = [MAX_WALLET_CONFIG_SEED, mint.key().as_ref()],
-        bump
+        bump = config.bump,
+        has_one = authority
     )]
     pub config: Account<'info, MaxWalletConfig>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [POOL_ACCOUNT_SEED, mint.key().as_ref(), token_account.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, PoolAccount>,
     /// CHECK: Extra account meta PDA derived by the interface
     #[account(mut)]
     pub extra_account_metas: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateMaxWalletConfig<'info> {
+pub struct InitializeHookStats<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
     pub authority: Signer<'info>,
     /// CHECK: The Token-2022 mint
     pub mint: UncheckedAccount<'info>,
@@ -389,6 +1933,18 @@ pub struct UpdateMaxWalletConfig<'info> {
         has_one = authority
     )]
     pub config: Account<'info, MaxWalletConfig>,
+    #[account(
+        init,
+        payer = payer,
+        space = HookStats::LEN,
+        seeds = [HOOK_STATS_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub stats: Account<'info, HookStats>,
+    /// CHECK: Extra account meta PDA derived by the interface
+    #[account(mut)]
+    pub extra_account_metas: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -411,23 +1967,383 @@ pub struct TransferHookExecute<'info> {
 }
 
 #[account]
+#[derive(Default)]
 pub struct MaxWalletConfig {
     pub authority: Pubkey,
     pub max_wallet_bps: u16,
     pub bump: u8,
-    pub reserved: [u8; 5],
+    /// Per-transfer cap in basis points of supply, 0 to disable.
+    pub max_tx_bps: u16,
+    pub transfer_policy: TransferPolicy,
+    /// Layout version, 0 for configs written before versioning. Older layouts
+    /// are shorter and need `migrate_max_wallet_config`.
+    pub version: u8,
+    /// Whether `execute` writes the mint's `HookStats`.
+    pub stats_enabled: bool,
+    /// Unix timestamp before which only exempt wallets can transfer, 0 for open.
+    pub trading_enabled_at: i64,
+    /// Seconds after `trading_enabled_at` during which the launch limits apply.
+    pub launch_window_secs: u32,
+    /// Starting max-wallet during the launch window, 0 for no tightening.
+    pub launch_max_wallet_bps: u16,
+    /// Starting max-tx during the launch window, 0 for no tightening.
+    pub launch_max_tx_bps: u16,
+    /// Minimum seconds between outgoing transfers of the same wallet, 0 to disable.
+    /// Senders other than exempt owners and registered pools need a `WalletState`.
+    pub cooldown_secs: u32,
+    /// Max a wallet can send per rolling window in basis points of supply, 0 to disable.
+    /// Senders other than exempt owners and registered pools need a `WalletState`.
+    pub max_volume_bps: u16,
+    /// Length of the volume window, 0 for 24 hours.
+    pub volume_window_secs: u32,
+    /// May pause and unpause alongside the authority, default key for none.
+    pub guardian: Pubkey,
+    pub paused: bool,
+    /// `PolicyKind` bits of the rules `execute` runs; the meta list is derived from it.
+    pub policies: u16,
+    /// Transfer-hook program `execute` is forwarded to, default key for none.
+    pub downstream_hook: Pubkey,
+    /// Extra metas of `downstream_hook` copied into this mint's meta list.
+    pub downstream_meta_count: u8,
+    /// Registered `PoolAccount`s; buys and sells are only told apart while non-zero.
+    pub pool_count: u16,
+    /// Max-tx for transfers out of a pool, 0 to use `max_tx_bps`.
+    pub buy_max_tx_bps: u16,
+    /// Max-tx for transfers into a pool, 0 to use `max_tx_bps`.
+    pub sell_max_tx_bps: u16,
+    /// Max-wallet for the buyer of a transfer out of a pool, 0 to use `max_wallet_bps`.
+    pub buy_max_wallet_bps: u16,
+    /// Per-transfer cap in raw token units, 0 to disable. Applies alongside
+    /// the bps limits; the lower one wins.
+    pub max_tx_amount: u64,
+    /// Zeroed space for future settings. New fields are carved from the front
+    /// and bump `MAX_WALLET_CONFIG_VERSION`, so 0 must mean disabled.
+    pub reserved_tail: [u64; 15],
 }
 
 impl MaxWalletConfig {
-    pub const LEN: usize = 8 /*disc*/ + 32 + 2 + 1 + 5;
+    pub const LEN: usize = 8 /*disc*/ + 32 + 2 + 1 + 2 + 1 + 1 + 1 + 8 + 4 + 2 + 2 + 4 + 2 + 4 + 32 + 1 + 2 + 32 + 1 + 2 + 2 + 2 + 2 + 8 + 8 * 15;
+
+    /// Recompute `policies` from the settings, returning whether it changed.
+    fn sync_policies(&mut self) -> bool {
+        let policies = policies::enabled_policies(self);
+        let changed = policies != self.policies;
+        self.policies = policies;
+        changed
+    }
+
+    fn can_pause(&self, signer: &Pubkey) -> bool {
+        *signer == self.authority
+            || (self.guardian != Pubkey::default() && *signer == self.guardian)
+    }
+
+    pub fn volume_window(&self) -> i64 {
+        match self.volume_window_secs {
+            0 => DEFAULT_VOLUME_WINDOW_SECS as i64,
+            secs => secs as i64,
+        }
+    }
+}
+
+/// How `TransferListEntry` records are interpreted for a mint.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransferPolicy {
+    #[default]
+    Open,
+    /// Listed owners can neither send nor receive.
+    Blocklist,
+    /// Only listed owners (and the authority) can send or receive.
+    Allowlist,
+}
+
+/// An owner on a mint's blocklist or allowlist, depending on its policy.
+#[account]
+pub struct TransferListEntry {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub bump: u8,
+}
+
+impl TransferListEntry {
+    pub const LEN: usize = 8 /*disc*/ + 32 + 32 + 1;
+}
+
+#[event]
+pub struct PauseToggled {
+    pub mint: Pubkey,
+    pub paused: bool,
+    pub signer: Pubkey,
+}
+
+#[event]
+pub struct MaxWalletConfigMigrated {
+    pub mint: Pubkey,
+    pub version: u8,
+    /// `PolicyKind` bitmask after the change.
+    pub policies: u16,
+}
+
+#[event]
+pub struct MaxWalletConfigInitialized {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub max_wallet_bps: u16,
+    /// `PolicyKind` bitmask after the change.
+    pub policies: u16,
+}
+
+/// Carries the update as submitted; unset fields were left unchanged.
+#[event]
+pub struct MaxWalletConfigUpdated {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub update: MaxWalletConfigUpdate,
+    /// `PolicyKind` bitmask after the change.
+    pub policies: u16,
+}
+
+/// `program` is the default key when forwarding was turned off.
+#[event]
+pub struct DownstreamHookSet {
+    pub mint: Pubkey,
+    pub program: Pubkey,
+    pub meta_count: u8,
+}
+
+#[event]
+pub struct ExtraAccountMetasUpdated {
+    pub mint: Pubkey,
+    pub policies: u16,
+}
+
+#[event]
+pub struct MaxWalletExemptionChanged {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub exempt: bool,
+}
+
+#[event]
+pub struct TransferListEntryChanged {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub listed: bool,
+}
+
+#[event]
+pub struct PoolAccountChanged {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub registered: bool,
+    pub pool_count: u16,
+}
+
+#[event]
+pub struct WalletStateInitialized {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+}
+
+#[event]
+pub struct HookStatsInitialized {
+    pub mint: Pubkey,
+    /// `PolicyKind` bitmask after the change.
+    pub policies: u16,
+}
+
+/// Emitted by `execute` for every transfer that passed the policy chain.
+/// Self-transfers are not checked and emit nothing. Logged with `emit!`:
+/// `emit_cpi!` would need the event authority in every extra meta list and a
+/// self-CPI from inside Token-2022's CPI.
+#[event]
+pub struct HookTransferPassed {
+    pub mint: Pubkey,
+    /// Source token account.
+    pub source: Pubkey,
+    /// Destination token account.
+    pub destination: Pubkey,
+    pub amount: u64,
+    /// Amount credited after any transfer fee.
+    pub received: u64,
+    pub direction: Direction,
+    /// `PolicyKind` bitmask of the rules that ran.
+    pub policies: u16,
+}
+
+/// An AMM pool token account registered for a mint.
+#[account]
+pub struct PoolAccount {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub bump: u8,
+}
+
+impl PoolAccount {
+    pub const LEN: usize = 8 /*disc*/ + 32 + 32 + 1;
+}
+
+/// Per-mint counters written by `execute` after every transfer that passed the
+/// policy chain while `stats_enabled` is set, which write-locks this account
+/// in every transfer of the mint.
+/// Buys and sells are only told apart while pools are registered.
+#[account]
+pub struct HookStats {
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub buys: u64,
+    pub sells: u64,
+    pub wallet_transfers: u64,
+    pub buy_volume: u64,
+    pub sell_volume: u64,
+    /// Transfers checked by the hook.
+    pub transfers: u64,
+    pub volume: u128,
+    pub largest_transfer: u64,
+    pub last_slot: u64,
+    /// Transfers each rule passed, indexed by `PolicyKind` (`Stats` excluded).
+    pub rule_passes: [u64; 8],
+}
+
+impl HookStats {
+    pub const LEN: usize = 8 /*disc*/ + 32 + 1 + 8 + 8 + 8 + 8 + 8 + 8 + 16 + 8 + 8 + 8 * 8;
+}
+
+/// Marks a wallet owner as exempt from a mint's max-wallet cap.
+#[account]
+pub struct MaxWalletExemption {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub bump: u8,
+}
+
+impl MaxWalletExemption {
+    pub const LEN: usize = 8 /*disc*/ + 32 + 32 + 1;
+}
+
+/// Per-wallet hook state, written by `execute` on every non-exempt transfer.
+///
+/// Outgoing volume is tracked as a sliding window counter: the volume sent in
+/// the current fixed window plus the previous window's volume weighted by how
+/// much of it still overlaps the rolling window. Off-chain tools can compute
+/// the remaining allowance from these fields with the same formula.
+#[account]
+pub struct WalletState {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    /// Unix timestamp of the wallet's last outgoing transfer, 0 if none yet.
+    pub last_transfer_at: i64,
+    pub bump: u8,
+    /// Start of the current volume window, 0 if nothing was sent yet.
+    pub window_start: i64,
+    /// Amount sent since `window_start`.
+    pub window_volume: u64,
+    /// Amount sent in the window before `window_start`.
+    pub previous_window_volume: u64,
+}
+
+impl WalletState {
+    pub const LEN: usize = 8 /*disc*/ + 32 + 32 + 8 + 1 + 8 + 8 + 8;
+
+    /// Advance the window so that `now` falls inside the current one.
+    fn roll_window(&mut self, now: i64, window: i64) {
+        let elapsed = now.saturating_sub(self.window_start);
+        if self.window_start == 0 || elapsed >= window.saturating_mul(2) {
+            self.window_start = now;
+            self.window_volume = 0;
+            self.previous_window_volume = 0;
+        } else if elapsed >= window {
+            self.window_start += window;
+            self.previous_window_volume = self.window_volume;
+            self.window_volume = 0;
+        }
+    }
+
+    /// Volume sent within the rolling window ending at `now`.
+    pub fn rolling_volume(&self, now: i64, window: i64) -> u128 {
+        let mut state = self.clone();
+        state.roll_window(now, window);
+        let elapsed = now.saturating_sub(state.window_start).clamp(0, window);
+        let overlap = (window - elapsed) as u128;
+        state.previous_window_volume as u128 * overlap / window as u128
+            + state.window_volume as u128
+    }
+}
+
+/// Fields left `None` keep their current value.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct MaxWalletConfigUpdate {
+    pub max_wallet_bps: Option<u16>,
+    pub max_tx_bps: Option<u16>,
+    pub transfer_policy: Option<TransferPolicy>,
+    pub trading_enabled_at: Option<i64>,
+    pub launch_window_secs: Option<u32>,
+    pub launch_max_wallet_bps: Option<u16>,
+    pub launch_max_tx_bps: Option<u16>,
+    pub cooldown_secs: Option<u32>,
+    pub max_volume_bps: Option<u16>,
+    pub volume_window_secs: Option<u32>,
+    pub guardian: Option<Pubkey>,
+    pub buy_max_tx_bps: Option<u16>,
+    pub sell_max_tx_bps: Option<u16>,
+    pub buy_max_wallet_bps: Option<u16>,
+    pub stats_enabled: Option<bool>,
+    pub max_tx_amount: Option<u64>,
 }
 
 const EXECUTE_DISCRIMINATOR: [u8; 8] = [105, 37, 101, 197, 75, 251, 102, 26];
 
-fn process_execute(ctx: Context<TransferHookExecute>, amount: u64) -> Result<()> {
+fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+    ctx.accounts.config.paused = paused;
+    emit!(PauseToggled {
+        mint: ctx.accounts.mint.key(),
+        paused,
+        signer: ctx.accounts.signer.key(),
+    });
+    Ok(())
+}
+
+fn process_execute<'info>(
+    ctx: Context<'_, '_, '_, 'info, TransferHookExecute<'info>>,
+    amount: u64,
+) -> Result<()> {
+    run_policies(&ctx, amount)?;
+
+    let config = &ctx.accounts.config;
+    if config.downstream_hook != Pubkey::default() {
+        downstream::forward_execute(
+            config,
+            [
+                &ctx.accounts.source,
+                &ctx.accounts.mint,
+                &ctx.accounts.destination,
+                &ctx.accounts.authority,
+            ],
+            ctx.remaining_accounts,
+            amount,
+        )?;
+    }
+    Ok(())
+}
+
+/// Token-2022 sets `transferring` on both token accounts only for the duration
+/// of its hook CPI. Without this check anyone could call `execute` directly
+/// and write a victim's wallet state or the mint's stats.
+fn require_transferring(account: &StateWithExtensions<SplTokenAccount>) -> Result<()> {
+    let extension = account
+        .get_extension::<transfer_hook::TransferHookAccount>()
+        .map_err(|_| MaxWalletError::NotTransferring)?;
+    require!(
+        bool::from(extension.transferring),
+        MaxWalletError::NotTransferring
+    );
+    Ok(())
+}
+
+fn run_policies(ctx: &Context<TransferHookExecute>, amount: u64) -> Result<()> {
     let config = &ctx.accounts.config;
 
-    if config.max_wallet_bps == 0 || config.max_wallet_bps >= MAX_BPS {
+    // A self-transfer leaves the wallet's holdings unchanged
+    if ctx.accounts.source.key() == ctx.accounts.destination.key() {
         return Ok(());
     }
 
@@ -438,7 +2354,6 @@ fn process_execute(ctx: Context<TransferHookExecute>, amount: u64) -> Result<()>
         .map_err(|_| MaxWalletError::AccountBorrowFailed)?;
     let mint_state = StateWithExtensions::<SplMint>::unpack(&mint_data)
         .map_err(|_| MaxWalletError::InvalidMint)?;
-    let mint_base = mint_state.base;
 
     let destination_data = ctx
         .accounts
@@ -447,6 +2362,7 @@ fn process_execute(ctx: Context<TransferHookExecute>, amount: u64) -> Result<()>
         .map_err(|_| MaxWalletError::AccountBorrowFailed)?;
     let destination_state = StateWithExtensions::<SplTokenAccount>::unpack(&destination_data)
         .map_err(|_| MaxWalletError::InvalidTokenAccount)?;
+    require_transferring(&destination_state)?;
     let destination_base = destination_state.base;
 
     require_keys_eq!(
@@ -455,47 +2371,256 @@ fn process_execute(ctx: Context<TransferHookExecute>, amount: u64) -> Result<()>
         MaxWalletError::DestinationMintMismatch
     );
 
-    if destination_base.owner == config.authority {
+    let source_owner = {
+        let source_data = ctx
+            .accounts
+            .source
+            .try_borrow_data()
+            .map_err(|_| MaxWalletError::AccountBorrowFailed)?;
+        let source_state = StateWithExtensions::<SplTokenAccount>::unpack(&source_data)
+            .map_err(|_| MaxWalletError::InvalidTokenAccount)?;
+        require_transferring(&source_state)?;
+        source_state.base.owner
+    };
+
+    // Any transfer fee is withheld on the destination (TransferFeeAmount)
+    // instead of being credited to it.
+    let clock = Clock::get()?;
+    let fee = match mint_state.get_extension::<TransferFeeConfig>() {
+        Ok(fee_config) => fee_config
+            .calculate_epoch_fee(clock.epoch, amount)
+            .ok_or(MaxWalletError::NumericalOverflow)?,
+        Err(_) => 0,
+    };
+    let received = amount
+        .checked_sub(fee)
+        .ok_or(MaxWalletError::NumericalOverflow)?;
+
+    policies::run(HookTransfer {
+        config,
+        remaining_accounts: ctx
+            .remaining_accounts
+            .get(downstream::segment_len(config)..)
+            .unwrap_or_default(),
+        source: ctx.accounts.source.key(),
+        destination: ctx.accounts.destination.key(),
+        mint: destination_base.mint,
+        supply: mint_state.base.supply,
+        amount,
+        received,
+        source_owner,
+        destination_owner: destination_base.owner,
+        destination_balance: destination_base.amount,
+        now: clock.unix_timestamp,
+        slot: clock.slot,
+        direction: Direction::WalletToWallet,
+    })
+}
+
+/// `bps` of `supply`, rounded up and never below 1 for a non-empty supply.
+fn bps_of_supply(supply: u64, bps: u16) -> Result<u128> {
+    let scaled = (supply as u128)
+        .checked_mul(bps as u128)
+        .ok_or(MaxWalletError::NumericalOverflow)?
+        .checked_add((MAX_BPS as u128) - 1)
+        .ok_or(MaxWalletError::NumericalOverflow)?;
+
+    let mut value = scaled / (MAX_BPS as u128);
+    if value == 0 && bps > 0 && supply > 0 {
+        value = 1;
+    }
+    Ok(value)
+}
+
+/// Creates `account` owned by `owner`, tolerating lamports already sent to the
+/// address. Empty `signer_seeds` means `account` signs the transaction itself.
+fn create_account_with_seeds<'info>(
+    payer: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    lamports: u64,
+    owner: &Pubkey,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let signers: &[&[&[u8]]] = if signer_seeds.is_empty() {
+        &[]
+    } else {
+        &[signer_seeds]
+    };
+    let current_lamports = account.lamports();
+    if current_lamports == 0 {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                account.key,
+                lamports,
+                space as u64,
+                owner,
+            ),
+            &[payer.clone(), account.clone(), system_program.clone()],
+            signers,
+        )?;
         return Ok(());
     }
 
-    let cap = {
-        let scaled = (mint_base.supply as u128)
-            .checked_mul(config.max_wallet_bps as u128)
-            .ok_or(MaxWalletError::NumericalOverflow)?
-            .checked_add((MAX_BPS as u128) - 1)
-            .ok_or(MaxWalletError::NumericalOverflow)?;
+    if lamports > current_lamports {
+        anchor_lang::solana_program::program::invoke(
+            &system_instruction::transfer(payer.key, account.key, lamports - current_lamports),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(account.key, space as u64),
+        &[account.clone(), system_program.clone()],
+        signers,
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account.key, owner),
+        &[account.clone(), system_program.clone()],
+        signers,
+    )?;
+    Ok(())
+}
+
+/// Moves one of the mint's authorities away from `current_authority`, which must sign.
+fn set_mint_authority<'info>(
+    mint: &AccountInfo<'info>,
+    current_authority: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    authority_type: AuthorityType,
+    new_authority: Option<&Pubkey>,
+) -> Result<()> {
+    anchor_lang::solana_program::program::invoke(
+        &set_authority(
+            token_program.key,
+            mint.key,
+            new_authority,
+            authority_type,
+            current_authority.key,
+            &[],
+        )?,
+        &[mint.clone(), current_authority.clone(), token_program.clone()],
+    )?;
+    Ok(())
+}
 
-        let mut value = scaled / (MAX_BPS as u128);
-        if value == 0 && config.max_wallet_bps > 0 && mint_base.supply > 0 {
-            value = 1;
+/// Creates (if needed) and writes the `ExtraAccountMetaList` PDA that tells
+/// Token-2022 which accounts `execute` needs.
+fn write_extra_account_metas<'info>(
+    payer: &AccountInfo<'info>,
+    extra_meta_info: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    mint_key: &Pubkey,
+    program_id: &Pubkey,
+    policies: u16,
+    downstream: &[ExtraAccountMeta],
+) -> Result<()> {
+    let (extra_meta_address, extra_meta_bump) =
+        spl_transfer_hook_interface::get_extra_account_metas_address_and_bump_seed(
+            mint_key, program_id,
+        );
+    require_keys_eq!(
+        extra_meta_address,
+        extra_meta_info.key(),
+        MaxWalletError::InvalidExtraAccountMetaAccount
+    );
+
+    let extra_metas = policies::extra_account_metas(policies, downstream)?;
+    let extra_meta_space = ExtraAccountMetaList::size_of(extra_metas.len())
+        .map_err(|_| MaxWalletError::ExtraAccountMetaSerialization)?;
+    let rent = Rent::get()?;
+    let required_lamports = rent.minimum_balance(extra_meta_space);
+
+    if extra_meta_info.owner != program_id || extra_meta_info.data_len() == 0 {
+        create_account_with_seeds(
+            payer,
+            extra_meta_info,
+            system_program,
+            extra_meta_space,
+            required_lamports,
+            program_id,
+            &collect_extra_account_metas_signer_seeds(mint_key, &[extra_meta_bump]),
+        )?;
+    } else if extra_meta_info.data_len() < extra_meta_space {
+        // Lists written by older versions hold fewer metas and must grow first
+        let shortfall = required_lamports.saturating_sub(extra_meta_info.lamports());
+        if shortfall > 0 {
+            anchor_lang::solana_program::program::invoke(
+                &system_instruction::transfer(&payer.key(), &extra_meta_address, shortfall),
+                &[payer.clone(), extra_meta_info.clone(), system_program.clone()],
+            )?;
         }
-        value
-    };
+        extra_meta_info.resize(extra_meta_space)?;
+    }
 
-    let current_balance = destination_base.amount as u128;
-    let transfer_amount = amount as u128;
-    let (pre_balance, post_balance) = if current_balance >= transfer_amount {
-        (current_balance - transfer_amount, current_balance)
+    let mut data = extra_meta_info
+        .try_borrow_mut_data()
+        .map_err(|_| MaxWalletError::ExtraAccountMetaSerialization)?;
+    let result = if data.iter().all(|byte| *byte == 0) {
+        ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &extra_metas)
     } else {
-        (
-            current_balance,
-            current_balance
-                .checked_add(transfer_amount)
-                .ok_or(MaxWalletError::NumericalOverflow)?,
-        )
+        ExtraAccountMetaList::update::<ExecuteInstruction>(&mut data, &extra_metas)
     };
+    result.map_err(|_| MaxWalletError::ExtraAccountMetaSerialization)?;
 
-    msg!(
-        "max_wallet_bps: {}, cap: {}, pre_balance: {}, amount: {}, post_balance: {}",
-        config.max_wallet_bps,
-        cap,
-        pre_balance,
-        amount,
-        post_balance
+    Ok(())
+}
+
+/// Rewrites the meta list after `config.policies` changed, keeping the
+/// downstream hook segment as stored.
+fn rewrite_extra_account_metas<'info>(
+    payer: &AccountInfo<'info>,
+    extra_meta_info: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    config: &MaxWalletConfig,
+    mint_key: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let downstream = downstream::stored_segment(config, extra_meta_info)?;
+    write_extra_account_metas(
+        payer,
+        extra_meta_info,
+        system_program,
+        mint_key,
+        program_id,
+        config.policies,
+        &downstream,
+    )
+}
+
+/// Creates the `MaxWalletConfig` PDA for a mint outside of an Anchor `init`
+/// constraint, for use when the mint itself is created in the same instruction.
+fn create_max_wallet_config<'info>(
+    payer: &AccountInfo<'info>,
+    config_info: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    mint_key: &Pubkey,
+    program_id: &Pubkey,
+    mut config: MaxWalletConfig,
+) -> Result<()> {
+    let (config_address, bump) =
+        Pubkey::find_program_address(&[MAX_WALLET_CONFIG_SEED, mint_key.as_ref()], program_id);
+    require_keys_eq!(
+        config_address,
+        config_info.key(),
+        MaxWalletError::InvalidMaxWalletConfigAccount
     );
 
-    require!(post_balance <= cap, MaxWalletError::MaxWalletExceeded);
+    let rent = Rent::get()?;
+    create_account_with_seeds(
+        payer,
+        config_info,
+        system_program,
+        MaxWalletConfig::LEN,
+        rent.minimum_balance(MaxWalletConfig::LEN),
+        program_id,
+        &[MAX_WALLET_CONFIG_SEED, mint_key.as_ref(), &[bump]],
+    )?;
+
+    config.bump = bump;
+    let mut data = config_info.try_borrow_mut_data()?;
+    config.try_serialize(&mut &mut data[..])?;
 
     Ok(())
 }
@@ -554,11 +2679,13 @@ pub struct ClaimReflection<'info> {
     pub user_claim_state: Account<'info, UserClaimState>,
     /// CHECK: Treasury authority that holds the reflection pool
     pub treasury_authority: Signer<'info>,
+    #[account(address = reflection_config.reward_token_mint)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
     #[account(mut)]
     pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(mut)]
     pub user_token_account: InterfaceAccount<'info, TokenAccount>,
-    pub token_program: Program<'info, Token2022>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
@@ -576,6 +2703,35 @@ impl ReflectionConfig {
     pub const LEN: usize = 8 /*disc*/ + 32 + 32 + 8 + 2 + 8 + 1;  // Added 32 bytes for reward_token_mint
 }
 
+#[event]
+pub struct ReflectionConfigInitialized {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub reward_token_mint: Pubkey,
+    pub min_holding: u64,
+    pub gas_rebate_bps: u16,
+}
+
+/// Settings after the update.
+#[event]
+pub struct ReflectionConfigUpdated {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub min_holding: u64,
+    pub gas_rebate_bps: u16,
+}
+
+#[event]
+pub struct ReflectionClaimed {
+    pub mint: Pubkey,
+    pub user: Pubkey,
+    pub gross_amount: u64,
+    pub gas_rebate: u64,
+    /// Transferred to the user: `gross_amount - gas_rebate`.
+    pub net_amount: u64,
+    pub timestamp: i64,
+}
+
 #[account]
 pub struct UserClaimState {
     pub user: Pubkey,
@@ -588,6 +2744,9 @@ impl UserClaimState {
     pub const LEN: usize = 8 /*disc*/ + 32 + 32 + 8 + 8;
 }
 
+// MaxWalletError and ReflectionError keep the codes they shipped with, both
+// starting at 6000, since existing clients match on them. Newer enums get
+// their own ranges: CreateTokenError 6100, MintControlError 6200.
 #[error_code]
 pub enum MaxWalletError {
     #[msg("Maximum wallet basis points must be 0-10,000")]
@@ -610,6 +2769,124 @@ pub enum MaxWalletError {
     NumericalOverflow,
     #[msg("Transfer exceeds the maximum allowed wallet allocation")]
     MaxWalletExceeded,
+    #[msg("Invalid max wallet config account")]
+    InvalidMaxWalletConfigAccount,
+    #[msg("Destination balance is lower than the amount received")]
+    BalanceMismatch,
+    #[msg("Maximum transaction basis points must be 0-10,000")]
+    InvalidMaxTxBps,
+    #[msg("Transfer exceeds the maximum allowed transaction size")]
+    MaxTxExceeded,
+    #[msg("Source wallet is blocklisted")]
+    SourceBlocked,
+    #[msg("Destination wallet is blocklisted")]
+    DestinationBlocked,
+    #[msg("Source wallet is not on the allowlist")]
+    SourceNotAllowed,
+    #[msg("Destination wallet is not on the allowlist")]
+    DestinationNotAllowed,
+    #[msg("Trading is not enabled yet")]
+    TradingNotEnabled,
+    #[msg("Wallet state account must be initialized while a cooldown or volume limit is set")]
+    WalletStateNotInitialized,
+    #[msg("Wallet state does not belong to this mint and owner")]
+    InvalidWalletState,
+    #[msg("Wallet is still in its transfer cooldown")]
+    CooldownActive,
+    #[msg("Maximum volume basis points must be 0-10,000")]
+    InvalidMaxVolumeBps,
+    #[msg("Transfer exceeds the wallet's rolling volume limit")]
+    VolumeLimitExceeded,
+    #[msg("Transfers are paused")]
+    TransfersPaused,
+    #[msg("Signer is neither the authority nor the guardian")]
+    Unauthorized,
+    #[msg("Downstream hook program is invalid or does not match the config")]
+    InvalidDownstreamHook,
+    #[msg("Downstream hook accounts are missing")]
+    MissingDownstreamAccounts,
+    #[msg("Downstream extra account metas are invalid")]
+    InvalidDownstreamExtraAccountMetas,
+    #[msg("Max wallet config already uses the current layout")]
+    ConfigAlreadyMigrated,
+    #[msg("Execute can only run during a Token-2022 transfer")]
+    NotTransferring,
+}
+
+#[error_code(offset = 6100)]
+pub enum CreateTokenError {
+    #[msg("Token name exceeds 32 bytes")]
+    NameTooLong,
+    #[msg("Token symbol exceeds 10 bytes")]
+    SymbolTooLong,
+    #[msg("Token URI exceeds 200 bytes")]
+    UriTooLong,
+    #[msg("Failed to serialize token metadata")]
+    MetadataSerialization,
+    #[msg("Transfer fee basis points must be 0-10,000")]
+    InvalidTransferFeeBps,
+    #[msg("Requested mint extensions cannot be combined")]
+    IncompatibleExtensions,
+    #[msg("Transfer hook requires the max wallet config and extra account meta accounts")]
+    MissingTransferHookAccounts,
+    #[msg("Authority must not be the default public key")]
+    InvalidAuthority,
+    #[msg("Default frozen accounts require a freeze authority")]
+    FrozenWithoutFreezeAuthority,
+    #[msg("Token account is not the recipient's associated token account")]
+    InvalidRecipientTokenAccount,
+    #[msg("At most 10 genesis allocations are supported")]
+    TooManyAllocations,
+    #[msg("Remaining accounts must be an owner/token account pair per allocation")]
+    AllocationAccountsMismatch,
+    #[msg("Allocation basis points must be 0-10,000")]
+    InvalidAllocationBps,
+    #[msg("Allocations must sum exactly to the supply")]
+    AllocationSumMismatch,
+    #[msg("Controlled minting cannot be combined with revoking the mint authority")]
+    ConflictingMintAuthority,
+    #[msg("Maximum supply must be at least the initial supply")]
+    InvalidSupplyCap,
+    #[msg("Controlled minting requires the mint authority config account")]
+    MissingMintAuthorityConfig,
+    #[msg("Mint extensions require the Token-2022 program")]
+    ExtensionsRequireToken2022,
+    #[msg("Mint account already exists")]
+    MintAlreadyExists,
+    #[msg("Mint seed exceeds 32 bytes")]
+    MintSeedTooLong,
+    #[msg("Mint account does not match the PDA derived from the mint seed")]
+    InvalidMintAddress,
+    #[msg("Token name must not be empty")]
+    NameEmpty,
+    #[msg("Token symbol must not be empty")]
+    SymbolEmpty,
+    #[msg("Token symbol may only contain ASCII letters and digits")]
+    InvalidSymbolCharacters,
+    #[msg("Token URI must use https://, ipfs:// or ar://")]
+    InvalidUriScheme,
+    #[msg("Decimals must be 0-9")]
+    InvalidDecimals,
+    #[msg("Supply must be greater than zero")]
+    ZeroSupply,
+    #[msg("Supply is less than one whole token at the given decimals")]
+    SupplyBelowOneToken,
+    #[msg("Supply or allocation amounts overflow a u64")]
+    SupplyOverflow,
+}
+
+#[error_code(offset = 6200)]
+pub enum MintControlError {
+    #[msg("Invalid mint authority config account")]
+    InvalidMintAuthorityConfig,
+    #[msg("Invalid mint account")]
+    InvalidMint,
+    #[msg("Numerical overflow")]
+    NumericalOverflow,
+    #[msg("Minting would exceed the maximum supply")]
+    SupplyCapExceeded,
+    #[msg("Minting would exceed this epoch's emission limit")]
+    EpochEmissionExceeded,
 }
 
 #[error_code]
@@ -621,3 +2898,225 @@ pub enum ReflectionError {
     #[msg("Insufficient amount for gas rebate")]
     InsufficientAmount,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allocation(share: AllocationShare) -> Allocation {
+        Allocation {
+            owner: Pubkey::new_unique(),
+            share,
+        }
+    }
+
+    #[test]
+    fn bps_rounding_remainder_goes_to_the_last_bps_share() {
+        let allocations = [
+            allocation(AllocationShare::Bps(5_000)),
+            allocation(AllocationShare::Bps(5_000)),
+        ];
+        assert_eq!(
+            allocation_amounts(&allocations, 1_000_000_001).unwrap(),
+            vec![500_000_000, 500_000_001]
+        );
+
+        let allocations = [
+            allocation(AllocationShare::Bps(3_333)),
+            allocation(AllocationShare::Bps(3_333)),
+            allocation(AllocationShare::Bps(3_334)),
+        ];
+        assert_eq!(
+            allocation_amounts(&allocations, 1_000_003).unwrap(),
+            vec![333_300, 333_300, 333_403]
+        );
+    }
+
+    #[test]
+    fn exact_amounts_are_kept() {
+        let allocations = [
+            allocation(AllocationShare::Amount(600)),
+            allocation(AllocationShare::Amount(400)),
+        ];
+        assert_eq!(
+            allocation_amounts(&allocations, 1_000).unwrap(),
+            vec![600, 400]
+        );
+    }
+
+    #[test]
+    fn shares_must_cover_the_supply_exactly() {
+        let short_amounts = [allocation(AllocationShare::Amount(999))];
+        assert_eq!(
+            allocation_amounts(&short_amounts, 1_000).unwrap_err(),
+            CreateTokenError::AllocationSumMismatch.into()
+        );
+
+        // Rounding must not hide a split that only adds up to 9,999 bps
+        let short_bps = [
+            allocation(AllocationShare::Bps(5_000)),
+            allocation(AllocationShare::Bps(4_999)),
+        ];
+        assert_eq!(
+            allocation_amounts(&short_bps, 10_000).unwrap_err(),
+            CreateTokenError::AllocationSumMismatch.into()
+        );
+
+        let too_many_bps = [allocation(AllocationShare::Bps(MAX_BPS + 1))];
+        assert_eq!(
+            allocation_amounts(&too_many_bps, 1_000).unwrap_err(),
+            CreateTokenError::InvalidAllocationBps.into()
+        );
+    }
+
+    #[test]
+    fn allocation_amounts_that_overflow_u64_are_rejected() {
+        let allocations = [
+            allocation(AllocationShare::Amount(u64::MAX)),
+            allocation(AllocationShare::Amount(1)),
+        ];
+        assert_eq!(
+            allocation_amounts(&allocations, u64::MAX).unwrap_err(),
+            CreateTokenError::SupplyOverflow.into()
+        );
+    }
+
+    fn token_args(decimals: u8, supply: u64) -> TokenArgs {
+        TokenArgs {
+            name: "Token".to_string(),
+            symbol: "TKN".to_string(),
+            decimals,
+            supply,
+            uri: String::new(),
+            extensions: None,
+            mint_authority: Pubkey::new_unique(),
+            freeze_authority: None,
+            revoke_mint_authority: false,
+            revoke_freeze_authority: false,
+            allocations: vec![],
+            controlled_minting: None,
+            mint_seed: None,
+        }
+    }
+
+    #[test]
+    fn supply_is_checked_against_decimals() {
+        assert!(token_args(MAX_DECIMALS, 10u64.pow(MAX_DECIMALS as u32)).validate().is_ok());
+        assert!(token_args(MAX_DECIMALS, u64::MAX).validate().is_ok());
+        assert_eq!(
+            token_args(MAX_DECIMALS, 10u64.pow(MAX_DECIMALS as u32) - 1)
+                .validate()
+                .unwrap_err(),
+            CreateTokenError::SupplyBelowOneToken.into()
+        );
+        assert_eq!(
+            token_args(MAX_DECIMALS + 1, u64::MAX).validate().unwrap_err(),
+            CreateTokenError::InvalidDecimals.into()
+        );
+        assert_eq!(
+            token_args(0, 0).validate().unwrap_err(),
+            CreateTokenError::ZeroSupply.into()
+        );
+    }
+
+    fn metadata(update_authority: Pubkey) -> TokenMetadata {
+        TokenMetadata {
+            update_authority: Some(update_authority).try_into().unwrap(),
+            mint: Pubkey::new_unique(),
+            name: "Token".to_string(),
+            symbol: "TKN".to_string(),
+            uri: "https://example.com/token.json".to_string(),
+            additional_metadata: vec![],
+        }
+    }
+
+    #[test]
+    fn metadata_is_handed_to_a_separate_update_authority() {
+        let token_program_id = spl_token_2022::id();
+        let payer = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let metadata = metadata(authority);
+        let mint = metadata.mint;
+
+        let instructions = token_metadata_instructions(&token_program_id, &payer, metadata);
+        assert_eq!(instructions.len(), 2);
+        // Every account must be one `create_token` passes to the CPI
+        for account in instructions.iter().flat_map(|ix| &ix.accounts) {
+            assert!(account.pubkey == mint || account.pubkey == payer);
+        }
+        assert_eq!(
+            instructions[1],
+            spl_token_metadata_interface::instruction::update_authority(
+                &token_program_id,
+                &mint,
+                &payer,
+                Some(authority).try_into().unwrap(),
+            )
+        );
+    }
+
+    #[test]
+    fn payer_authority_keeps_the_metadata() {
+        let payer = Pubkey::new_unique();
+        let instructions =
+            token_metadata_instructions(&spl_token_2022::id(), &payer, metadata(payer));
+        assert_eq!(instructions.len(), 1);
+    }
+
+    fn wallet_state(
+        window_start: i64,
+        window_volume: u64,
+        previous_window_volume: u64,
+    ) -> WalletState {
+        WalletState {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            last_transfer_at: 0,
+            bump: 0,
+            window_start,
+            window_volume,
+            previous_window_volume,
+        }
+    }
+
+    #[test]
+    fn fresh_wallet_has_no_rolling_volume() {
+        assert_eq!(wallet_state(0, 0, 0).rolling_volume(1_000, 100), 0);
+    }
+
+    #[test]
+    fn rolling_volume_weights_the_previous_window_by_overlap() {
+        let state = wallet_state(1_000, 40, 100);
+        assert_eq!(state.rolling_volume(1_000, 100), 140);
+        assert_eq!(state.rolling_volume(1_025, 100), 115);
+        // Next window: 40 becomes the previous volume, weighted by 3/4
+        assert_eq!(state.rolling_volume(1_125, 100), 30);
+        // Two windows later nothing overlaps
+        assert_eq!(state.rolling_volume(1_200, 100), 0);
+    }
+
+    #[test]
+    fn roll_window_advances_or_resets() {
+        let window = |state: &WalletState| {
+            (
+                state.window_start,
+                state.window_volume,
+                state.previous_window_volume,
+            )
+        };
+
+        let mut state = wallet_state(1_000, 40, 100);
+        state.roll_window(1_050, 100);
+        assert_eq!(window(&state), (1_000, 40, 100));
+
+        state.roll_window(1_150, 100);
+        assert_eq!(window(&state), (1_100, 0, 40));
+
+        state.roll_window(1_400, 100);
+        assert_eq!(window(&state), (1_400, 0, 0));
+
+        let mut fresh = wallet_state(0, 0, 0);
+        fresh.roll_window(1_000, 100);
+        assert_eq!(fresh.window_start, 1_000);
+    }
+}
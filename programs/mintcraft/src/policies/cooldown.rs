@@ -0,0 +1,72 @@
+//! Minimum time between outgoing transfers of the same non-exempt wallet.
+//!
+//! Only the sender is checked and stamped: stamping the receiver would let
+//! anyone reset a holder's cooldown with dust, and would make every transfer
+//! to a wallet without a `WalletState` fail. Senders other than exempt owners
+//! and registered pools need a `WalletState`, created with
+//! `initialize_wallet_state`.
+
+use anchor_lang::prelude::*;
+
+use super::{update_wallet_state, HookAccount, HookTransfer};
+use crate::{MaxWalletConfig, MaxWalletError};
+
+pub(super) const ACCOUNTS: &[HookAccount] =
+    &[HookAccount::SourceExemption, HookAccount::SourceWalletState];
+
+pub(super) fn is_configured(config: &MaxWalletConfig) -> bool {
+    config.cooldown_secs > 0
+}
+
+pub(super) fn check(transfer: &HookTransfer) -> Result<()> {
+    if !transfer.sender_limited() {
+        return Ok(());
+    }
+
+    let cooldown_secs = transfer.config.cooldown_secs as i64;
+    update_wallet_state(
+        transfer.account(HookAccount::SourceWalletState),
+        &transfer.mint,
+        &transfer.source_owner,
+        |state| {
+            if state.last_transfer_at > 0 {
+                let elapsed = transfer.now.saturating_sub(state.last_transfer_at);
+                require!(elapsed >= cooldown_secs, MaxWalletError::CooldownActive);
+            }
+            state.last_transfer_at = transfer.now;
+            Ok(())
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policies::Direction;
+
+    fn config() -> MaxWalletConfig {
+        let mut config = MaxWalletConfig {
+            cooldown_secs: 60,
+            ..Default::default()
+        };
+        config.sync_policies();
+        config
+    }
+
+    #[test]
+    fn sender_without_wallet_state_is_rejected() {
+        let config = config();
+        assert_eq!(
+            check(&HookTransfer::for_test(&config, 1)).unwrap_err(),
+            MaxWalletError::WalletStateNotInitialized.into()
+        );
+    }
+
+    #[test]
+    fn buys_from_a_pool_need_no_wallet_state() {
+        let config = config();
+        let mut transfer = HookTransfer::for_test(&config, 1);
+        transfer.direction = Direction::Buy;
+        assert!(check(&transfer).is_ok());
+    }
+}
@@ -0,0 +1,47 @@
+//! Buy/sell detection from registered `PoolAccount`s. The direction feeds the
+//! max-tx/max-wallet overrides and the counters in `HookStats`.
+
+use anchor_lang::prelude::*;
+
+use super::{load_owner_record, HookAccount, HookTransfer};
+use crate::{MaxWalletConfig, PoolAccount};
+
+pub(super) const ACCOUNTS: &[HookAccount] =
+    &[HookAccount::SourcePool, HookAccount::DestinationPool];
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    WalletToWallet,
+    /// Out of a registered pool.
+    Buy,
+    /// Into a registered pool.
+    Sell,
+}
+
+impl Direction {
+    /// Pool-to-pool routing counts as a wallet-to-wallet transfer.
+    pub(super) fn classify(transfer: &HookTransfer) -> Self {
+        let is_pool = |account: HookAccount, token_account: &Pubkey| {
+            load_owner_record::<PoolAccount>(transfer.account(account)).is_some_and(|pool| {
+                pool.mint == transfer.mint && pool.token_account == *token_account
+            })
+        };
+        match (
+            is_pool(HookAccount::SourcePool, &transfer.source),
+            is_pool(HookAccount::DestinationPool, &transfer.destination),
+        ) {
+            (true, false) => Direction::Buy,
+            (false, true) => Direction::Sell,
+            _ => Direction::WalletToWallet,
+        }
+    }
+}
+
+pub(super) fn is_configured(config: &MaxWalletConfig) -> bool {
+    config.pool_count > 0
+}
+
+/// Never rejects; `run` classifies every transfer before the chain starts.
+pub(super) fn check(_transfer: &HookTransfer) -> Result<()> {
+    Ok(())
+}
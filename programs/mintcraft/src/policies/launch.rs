@@ -0,0 +1,104 @@
+//! Launch protection: nothing but exempt-to-exempt transfers before
+//! `trading_enabled_at`, then max-wallet/max-tx limits that start at their
+//! launch values and relax linearly to the normal ones over the launch window.
+
+use anchor_lang::prelude::*;
+
+use super::{HookAccount, HookTransfer};
+use crate::{MaxWalletConfig, MaxWalletError, MAX_BPS};
+
+pub(super) const ACCOUNTS: &[HookAccount] = &[
+    HookAccount::SourceExemption,
+    HookAccount::DestinationExemption,
+];
+
+pub(super) fn is_configured(config: &MaxWalletConfig) -> bool {
+    config.trading_enabled_at > 0
+}
+
+/// Whether a launch value tightens a limit at some point of the window.
+pub(super) fn tightens(config: &MaxWalletConfig, launch_bps: u16) -> bool {
+    config.trading_enabled_at > 0 && config.launch_window_secs > 0 && launch_bps > 0
+}
+
+/// Bps in force at `now` for a limit with the given launch and normal values.
+pub(super) fn effective_bps(
+    config: &MaxWalletConfig,
+    now: i64,
+    launch_bps: u16,
+    normal_bps: u16,
+) -> u16 {
+    let window = config.launch_window_secs as i64;
+    let elapsed = now.saturating_sub(config.trading_enabled_at);
+    if !tightens(config, launch_bps) || elapsed < 0 || elapsed >= window {
+        return normal_bps;
+    }
+
+    // A disabled normal limit relaxes towards "no limit"; a launch value looser
+    // than the normal one is clamped so the window never loosens a limit
+    let target = if normal_bps == 0 { MAX_BPS } else { normal_bps } as i64;
+    let start = (launch_bps as i64).min(target);
+    (start + (target - start) * elapsed / window) as u16
+}
+
+pub(super) fn check(transfer: &HookTransfer) -> Result<()> {
+    if transfer.now >= transfer.config.trading_enabled_at {
+        return Ok(());
+    }
+    // e.g. the authority seeding an exempt pool; buys out of that pool must wait
+    require!(
+        transfer.source_exempt() && transfer.destination_exempt(),
+        MaxWalletError::TradingNotEnabled
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> MaxWalletConfig {
+        MaxWalletConfig {
+            trading_enabled_at: 1_000,
+            launch_window_secs: 100,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn relaxes_linearly_over_the_window() {
+        let config = config();
+        assert_eq!(effective_bps(&config, 1_000, 50, 200), 50);
+        assert_eq!(effective_bps(&config, 1_050, 50, 200), 125);
+        assert_eq!(effective_bps(&config, 1_099, 50, 200), 198);
+        assert_eq!(effective_bps(&config, 1_100, 50, 200), 200);
+        assert_eq!(effective_bps(&config, 5_000, 50, 200), 200);
+    }
+
+    #[test]
+    fn disabled_normal_limit_relaxes_towards_no_limit() {
+        let config = config();
+        assert_eq!(effective_bps(&config, 1_000, 50, 0), 50);
+        assert_eq!(effective_bps(&config, 1_050, 50, 0), 5_025);
+        assert_eq!(effective_bps(&config, 1_100, 50, 0), 0);
+    }
+
+    #[test]
+    fn looser_launch_value_never_loosens_the_limit() {
+        let config = config();
+        assert_eq!(effective_bps(&config, 1_000, 300, 200), 200);
+        assert_eq!(effective_bps(&config, 1_050, 300, 200), 200);
+    }
+
+    #[test]
+    fn normal_limit_applies_outside_a_configured_window() {
+        assert_eq!(effective_bps(&config(), 1_000, 0, 200), 200);
+        assert_eq!(effective_bps(&config(), 900, 50, 200), 200);
+
+        let no_window = MaxWalletConfig {
+            launch_window_secs: 0,
+            ..config()
+        };
+        assert_eq!(effective_bps(&no_window, 1_000, 50, 200), 200);
+    }
+}
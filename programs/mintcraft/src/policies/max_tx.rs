@@ -0,0 +1,99 @@
+//! Per-transfer cap in basis points of supply, optionally different for buys
+//! and sells, and/or as an absolute amount. When both are set the lower one
+//! applies. Transfers to or from the authority are not limited.
+
+use anchor_lang::prelude::*;
+
+use super::{is_bps_limit, launch, Direction, HookAccount, HookTransfer};
+use crate::{bps_of_supply, MaxWalletConfig, MaxWalletError};
+
+pub(super) const ACCOUNTS: &[HookAccount] = &[];
+
+pub(super) fn is_configured(config: &MaxWalletConfig) -> bool {
+    is_bps_limit(config.max_tx_bps)
+        || is_bps_limit(config.buy_max_tx_bps)
+        || is_bps_limit(config.sell_max_tx_bps)
+        || launch::tightens(config, config.launch_max_tx_bps)
+        || config.max_tx_amount > 0
+}
+
+pub(super) fn check(transfer: &HookTransfer) -> Result<()> {
+    let config = transfer.config;
+    if transfer.authority_involved() {
+        return Ok(());
+    }
+
+    require!(
+        config.max_tx_amount == 0 || transfer.amount <= config.max_tx_amount,
+        MaxWalletError::MaxTxExceeded
+    );
+
+    let normal_bps = match transfer.direction {
+        Direction::Buy if config.buy_max_tx_bps > 0 => config.buy_max_tx_bps,
+        Direction::Sell if config.sell_max_tx_bps > 0 => config.sell_max_tx_bps,
+        _ => config.max_tx_bps,
+    };
+    let max_tx_bps =
+        launch::effective_bps(config, transfer.now, config.launch_max_tx_bps, normal_bps);
+    if !is_bps_limit(max_tx_bps) {
+        return Ok(());
+    }
+
+    let max_tx = bps_of_supply(transfer.supply, max_tx_bps)?;
+    require!(
+        (transfer.amount as u128) <= max_tx,
+        MaxWalletError::MaxTxExceeded
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn absolute_amount_caps_each_transfer() {
+        let config = MaxWalletConfig {
+            max_tx_amount: 500,
+            ..Default::default()
+        };
+        assert!(is_configured(&config));
+        assert!(check(&HookTransfer::for_test(&config, 500)).is_ok());
+        assert_eq!(
+            check(&HookTransfer::for_test(&config, 501)).unwrap_err(),
+            MaxWalletError::MaxTxExceeded.into()
+        );
+    }
+
+    #[test]
+    fn lower_of_bps_and_amount_applies() {
+        // 1% of the 1,000,000 supply is 10,000
+        let config = MaxWalletConfig {
+            max_tx_bps: 100,
+            max_tx_amount: 20_000,
+            ..Default::default()
+        };
+        assert!(check(&HookTransfer::for_test(&config, 10_000)).is_ok());
+        assert!(check(&HookTransfer::for_test(&config, 10_001)).is_err());
+
+        let config = MaxWalletConfig {
+            max_tx_bps: 100,
+            max_tx_amount: 5_000,
+            ..Default::default()
+        };
+        assert!(check(&HookTransfer::for_test(&config, 5_000)).is_ok());
+        assert!(check(&HookTransfer::for_test(&config, 5_001)).is_err());
+    }
+
+    #[test]
+    fn authority_is_not_capped() {
+        let config = MaxWalletConfig {
+            authority: Pubkey::new_unique(),
+            max_tx_amount: 500,
+            ..Default::default()
+        };
+        let mut transfer = HookTransfer::for_test(&config, 10_000);
+        transfer.source_owner = config.authority;
+        assert!(check(&transfer).is_ok());
+    }
+}
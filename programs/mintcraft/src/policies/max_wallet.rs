@@ -0,0 +1,62 @@
+//! Cap on a non-exempt destination's balance in basis points of supply,
+//! optionally different for buyers. Sells land in a registered pool, which is
+//! never capped.
+
+use anchor_lang::prelude::*;
+
+use super::{is_bps_limit, launch, Direction, HookAccount, HookTransfer};
+use crate::{bps_of_supply, MaxWalletConfig, MaxWalletError};
+
+pub(super) const ACCOUNTS: &[HookAccount] = &[HookAccount::DestinationExemption];
+
+pub(super) fn is_configured(config: &MaxWalletConfig) -> bool {
+    is_bps_limit(config.max_wallet_bps)
+        || is_bps_limit(config.buy_max_wallet_bps)
+        || launch::tightens(config, config.launch_max_wallet_bps)
+}
+
+pub(super) fn check(transfer: &HookTransfer) -> Result<()> {
+    if transfer.direction == Direction::Sell {
+        return Ok(());
+    }
+
+    let config = transfer.config;
+    let normal_bps = match transfer.direction {
+        Direction::Buy if config.buy_max_wallet_bps > 0 => config.buy_max_wallet_bps,
+        _ => config.max_wallet_bps,
+    };
+    let max_wallet_bps = launch::effective_bps(
+        config,
+        transfer.now,
+        config.launch_max_wallet_bps,
+        normal_bps,
+    );
+    if !is_bps_limit(max_wallet_bps) || transfer.destination_exempt() {
+        return Ok(());
+    }
+
+    let cap = bps_of_supply(transfer.supply, max_wallet_bps)?;
+
+    // Token-2022 invokes the hook after balances are updated, so the destination
+    // amount already is the post-transfer balance.
+    let post_balance = transfer.destination_balance;
+    let pre_balance = post_balance
+        .checked_sub(transfer.received)
+        .ok_or(MaxWalletError::BalanceMismatch)?;
+
+    log!(
+        "max_wallet_bps: {}, cap: {}, pre_balance: {}, amount: {}, post_balance: {}",
+        max_wallet_bps,
+        cap,
+        pre_balance,
+        transfer.amount,
+        post_balance
+    );
+
+    require!(
+        (post_balance as u128) <= cap,
+        MaxWalletError::MaxWalletExceeded
+    );
+
+    Ok(())
+}
@@ -0,0 +1,383 @@
+//! Transfer-hook policy chain. Each rule lives in its own module, declares the
+//! extra accounts it reads and runs in `PolicyKind` order from `execute`.
+
+use anchor_lang::prelude::*;
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, seeds::Seed};
+
+use crate::{
+    HookTransferPassed, MaxWalletConfig, MaxWalletError, MaxWalletExemption, WalletState,
+    HOOK_STATS_SEED, MAX_BPS, MAX_WALLET_CONFIG_SEED, MAX_WALLET_EXEMPTION_SEED, POOL_ACCOUNT_SEED,
+    TRANSFER_LIST_SEED, WALLET_STATE_SEED,
+};
+
+mod cooldown;
+mod direction;
+mod launch;
+mod max_tx;
+mod max_wallet;
+mod pause;
+mod stats;
+mod transfer_list;
+mod volume;
+
+pub use direction::Direction;
+
+/// Hook rules in evaluation order. The discriminant is the rule's bit in
+/// `MaxWalletConfig::policies`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PolicyKind {
+    Pause = 0,
+    Launch = 1,
+    TransferList = 2,
+    Cooldown = 3,
+    Volume = 4,
+    MaxTx = 5,
+    MaxWallet = 6,
+    Direction = 7,
+    /// Counts passed transfers; last so it only sees transfers every rule allowed.
+    Stats = 8,
+}
+
+impl PolicyKind {
+    pub const ALL: [PolicyKind; 9] = [
+        PolicyKind::Pause,
+        PolicyKind::Launch,
+        PolicyKind::TransferList,
+        PolicyKind::Cooldown,
+        PolicyKind::Volume,
+        PolicyKind::MaxTx,
+        PolicyKind::MaxWallet,
+        PolicyKind::Direction,
+        PolicyKind::Stats,
+    ];
+
+    pub const fn bit(self) -> u16 {
+        1 << self as u16
+    }
+
+    /// Whether the config turns this rule on.
+    fn is_configured(self, config: &MaxWalletConfig) -> bool {
+        match self {
+            PolicyKind::Pause => pause::is_configured(config),
+            PolicyKind::Launch => launch::is_configured(config),
+            PolicyKind::TransferList => transfer_list::is_configured(config),
+            PolicyKind::Cooldown => cooldown::is_configured(config),
+            PolicyKind::Volume => volume::is_configured(config),
+            PolicyKind::MaxTx => max_tx::is_configured(config),
+            PolicyKind::MaxWallet => max_wallet::is_configured(config),
+            PolicyKind::Direction => direction::is_configured(config),
+            PolicyKind::Stats => stats::is_configured(config),
+        }
+    }
+
+    fn accounts(self) -> &'static [HookAccount] {
+        match self {
+            PolicyKind::Pause => pause::ACCOUNTS,
+            PolicyKind::Launch => launch::ACCOUNTS,
+            PolicyKind::TransferList => transfer_list::ACCOUNTS,
+            PolicyKind::Cooldown => cooldown::ACCOUNTS,
+            PolicyKind::Volume => volume::ACCOUNTS,
+            PolicyKind::MaxTx => max_tx::ACCOUNTS,
+            PolicyKind::MaxWallet => max_wallet::ACCOUNTS,
+            PolicyKind::Direction => direction::ACCOUNTS,
+            PolicyKind::Stats => stats::ACCOUNTS,
+        }
+    }
+
+    fn check(self, transfer: &HookTransfer) -> Result<()> {
+        match self {
+            PolicyKind::Pause => pause::check(transfer),
+            PolicyKind::Launch => launch::check(transfer),
+            PolicyKind::TransferList => transfer_list::check(transfer),
+            PolicyKind::Cooldown => cooldown::check(transfer),
+            PolicyKind::Volume => volume::check(transfer),
+            PolicyKind::MaxTx => max_tx::check(transfer),
+            PolicyKind::MaxWallet => max_wallet::check(transfer),
+            PolicyKind::Direction => direction::check(transfer),
+            PolicyKind::Stats => stats::check(transfer),
+        }
+    }
+}
+
+/// Bitmask of the rules the config turns on.
+pub fn enabled_policies(config: &MaxWalletConfig) -> u16 {
+    PolicyKind::ALL
+        .iter()
+        .filter(|policy| policy.is_configured(config))
+        .fold(0, |policies, policy| policies | policy.bit())
+}
+
+/// Seed-derived accounts a policy can ask for, in `ExtraAccountMetaList` order.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HookAccount {
+    DestinationExemption = 0,
+    SourceListEntry = 1,
+    DestinationListEntry = 2,
+    SourceExemption = 3,
+    SourceWalletState = 4,
+    DestinationWalletState = 5,
+    SourcePool = 6,
+    DestinationPool = 7,
+    Stats = 8,
+}
+
+impl HookAccount {
+    const ALL: [HookAccount; 9] = [
+        HookAccount::DestinationExemption,
+        HookAccount::SourceListEntry,
+        HookAccount::DestinationListEntry,
+        HookAccount::SourceExemption,
+        HookAccount::SourceWalletState,
+        HookAccount::DestinationWalletState,
+        HookAccount::SourcePool,
+        HookAccount::DestinationPool,
+        HookAccount::Stats,
+    ];
+
+    const fn bit(self) -> u16 {
+        1 << self as u16
+    }
+
+    /// PDA of `[seed, mint, key]` where `key` is the owner of the source
+    /// (index 0) or destination (index 2) token account, the token account
+    /// itself for pool records, or nothing for the stats account.
+    fn meta(self) -> Result<ExtraAccountMeta> {
+        let owner_of = |account_index| Seed::AccountData {
+            account_index,
+            data_index: 32, // owner
+            length: 32,
+        };
+        let (seed, key, is_writable) = match self {
+            HookAccount::DestinationExemption => {
+                (MAX_WALLET_EXEMPTION_SEED, Some(owner_of(2)), false)
+            }
+            HookAccount::SourceListEntry => (TRANSFER_LIST_SEED, Some(owner_of(0)), false),
+            HookAccount::DestinationListEntry => (TRANSFER_LIST_SEED, Some(owner_of(2)), false),
+            HookAccount::SourceExemption => (MAX_WALLET_EXEMPTION_SEED, Some(owner_of(0)), false),
+            HookAccount::SourceWalletState => (WALLET_STATE_SEED, Some(owner_of(0)), true),
+            HookAccount::DestinationWalletState => (WALLET_STATE_SEED, Some(owner_of(2)), true),
+            HookAccount::SourcePool => (
+                POOL_ACCOUNT_SEED,
+                Some(Seed::AccountKey { index: 0 }),
+                false,
+            ),
+            HookAccount::DestinationPool => (
+                POOL_ACCOUNT_SEED,
+                Some(Seed::AccountKey { index: 2 }),
+                false,
+            ),
+            HookAccount::Stats => (HOOK_STATS_SEED, None, true),
+        };
+
+        let mut seeds = vec![
+            Seed::Literal {
+                bytes: seed.to_vec(),
+            },
+            Seed::AccountKey { index: 1 }, // mint account
+        ];
+        seeds.extend(key);
+        let meta = ExtraAccountMeta::new_with_seeds(&seeds, false, is_writable)
+            .map_err(|_| MaxWalletError::ExtraAccountMetaSerialization)?;
+        Ok(meta)
+    }
+}
+
+/// Bitmask of the `HookAccount`s the given policies need.
+fn required_accounts(policies: u16) -> u16 {
+    PolicyKind::ALL
+        .iter()
+        .filter(|policy| policies & policy.bit() != 0)
+        .flat_map(|policy| policy.accounts())
+        .fold(0, |accounts, account| accounts | account.bit())
+}
+
+/// Accounts appended to every `execute`, in order after the extra meta list:
+/// the config, the downstream hook segment (if any), then whatever the enabled
+/// policies need.
+pub fn extra_account_metas(
+    policies: u16,
+    downstream: &[ExtraAccountMeta],
+) -> Result<Vec<ExtraAccountMeta>> {
+    let config = ExtraAccountMeta::new_with_seeds(
+        &[
+            Seed::Literal {
+                bytes: MAX_WALLET_CONFIG_SEED.to_vec(),
+            },
+            Seed::AccountKey { index: 1 }, // mint account
+        ],
+        false,
+        false,
+    )
+    .map_err(|_| MaxWalletError::ExtraAccountMetaSerialization)?;
+
+    let required = required_accounts(policies);
+    let mut metas = vec![config];
+    metas.extend_from_slice(downstream);
+    for account in HookAccount::ALL {
+        if required & account.bit() != 0 {
+            metas.push(account.meta()?);
+        }
+    }
+    Ok(metas)
+}
+
+/// What each policy sees of a transfer.
+pub struct HookTransfer<'a, 'info> {
+    pub config: &'a MaxWalletConfig,
+    /// Accounts after the config and downstream segment, laid out by `extra_account_metas`.
+    pub remaining_accounts: &'a [AccountInfo<'info>],
+    /// Source token account.
+    pub source: Pubkey,
+    /// Destination token account.
+    pub destination: Pubkey,
+    pub mint: Pubkey,
+    pub supply: u64,
+    pub amount: u64,
+    /// Amount credited to the destination after any transfer fee.
+    pub received: u64,
+    pub source_owner: Pubkey,
+    pub destination_owner: Pubkey,
+    /// Destination balance after the transfer.
+    pub destination_balance: u64,
+    pub now: i64,
+    pub slot: u64,
+    /// Filled in by `run` from the registered pools.
+    pub direction: Direction,
+}
+
+impl<'info> HookTransfer<'_, 'info> {
+    fn account(&self, account: HookAccount) -> Option<&AccountInfo<'info>> {
+        let required = required_accounts(self.config.policies);
+        if required & account.bit() == 0 {
+            return None;
+        }
+        let index = (required & (account.bit() - 1)).count_ones() as usize;
+        self.remaining_accounts.get(index)
+    }
+
+    fn authority_involved(&self) -> bool {
+        self.source_owner == self.config.authority
+            || self.destination_owner == self.config.authority
+    }
+
+    /// The authority, or an owner with a `MaxWalletExemption` (pools,
+    /// treasuries and vaults registered by the authority).
+    fn is_exempt(&self, exemption: HookAccount, owner: &Pubkey) -> bool {
+        *owner == self.config.authority
+            || load_owner_record::<MaxWalletExemption>(self.account(exemption))
+                .is_some_and(|record| record.mint == self.mint && record.owner == *owner)
+    }
+
+    fn source_exempt(&self) -> bool {
+        self.is_exempt(HookAccount::SourceExemption, &self.source_owner)
+    }
+
+    fn destination_exempt(&self) -> bool {
+        self.is_exempt(HookAccount::DestinationExemption, &self.destination_owner)
+    }
+
+    /// Whether the per-wallet limits apply to the sender. Exempt owners and
+    /// registered pools are skipped: a pool has no `WalletState`, and requiring
+    /// one would fail every buy.
+    fn sender_limited(&self) -> bool {
+        self.direction != Direction::Buy && !self.source_exempt()
+    }
+}
+
+#[cfg(test)]
+impl<'a> HookTransfer<'a, '_> {
+    /// A wallet-to-wallet transfer of `amount` with a supply of 1,000,000 and
+    /// no extra accounts.
+    pub(super) fn for_test(config: &'a MaxWalletConfig, amount: u64) -> Self {
+        HookTransfer {
+            config,
+            remaining_accounts: &[],
+            source: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            supply: 1_000_000,
+            amount,
+            received: amount,
+            source_owner: Pubkey::new_unique(),
+            destination_owner: Pubkey::new_unique(),
+            destination_balance: amount,
+            now: 0,
+            slot: 0,
+            direction: Direction::WalletToWallet,
+        }
+    }
+}
+
+/// Runs every enabled policy in order, stopping at the first rejection, then
+/// emits `HookTransferPassed`.
+pub fn run(mut transfer: HookTransfer) -> Result<()> {
+    transfer.direction = Direction::classify(&transfer);
+    for policy in PolicyKind::ALL {
+        if transfer.config.policies & policy.bit() != 0 {
+            policy.check(&transfer)?;
+        }
+    }
+
+    emit!(HookTransferPassed {
+        mint: transfer.mint,
+        source: transfer.source,
+        destination: transfer.destination,
+        amount: transfer.amount,
+        received: transfer.received,
+        direction: transfer.direction,
+        policies: transfer.config.policies,
+    });
+    Ok(())
+}
+
+fn is_bps_limit(bps: u16) -> bool {
+    bps > 0 && bps < MAX_BPS
+}
+
+/// Loads a per-owner record passed to `execute`. The PDA may not exist (or may
+/// be missing from older meta lists), which callers treat as "no record".
+fn load_owner_record<T: AccountDeserialize>(account: Option<&AccountInfo>) -> Option<T> {
+    let account = account?;
+    if account.owner != &crate::ID || account.data_is_empty() {
+        return None;
+    }
+    let data = account.try_borrow_data().ok()?;
+    T::try_deserialize(&mut &data[..]).ok()
+}
+
+/// Deserializes a program-owned record, applies `update` and writes it back.
+fn update_record<T: AccountSerialize + AccountDeserialize>(
+    account: &AccountInfo,
+    update: impl FnOnce(&mut T) -> Result<()>,
+) -> Result<()> {
+    let mut data = account
+        .try_borrow_mut_data()
+        .map_err(|_| MaxWalletError::AccountBorrowFailed)?;
+    let mut record = T::try_deserialize(&mut &data[..])?;
+    update(&mut record)?;
+    record.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
+
+/// Applies `update` to an owner's `WalletState` and writes it back. The state
+/// must exist while a policy that tracks wallets is enabled.
+fn update_wallet_state(
+    account: Option<&AccountInfo>,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    update: impl FnOnce(&mut WalletState) -> Result<()>,
+) -> Result<()> {
+    let account = account.ok_or(MaxWalletError::WalletStateNotInitialized)?;
+    require!(
+        account.owner == &crate::ID && !account.data_is_empty(),
+        MaxWalletError::WalletStateNotInitialized
+    );
+
+    update_record(account, |state: &mut WalletState| {
+        require!(
+            state.mint == *mint && state.owner == *owner,
+            MaxWalletError::InvalidWalletState
+        );
+        update(state)
+    })
+}
@@ -0,0 +1,27 @@
+//! Emergency stop: while the config is paused only exempt wallets can transfer.
+
+use anchor_lang::prelude::*;
+
+use super::{HookAccount, HookTransfer};
+use crate::{MaxWalletConfig, MaxWalletError};
+
+pub(super) const ACCOUNTS: &[HookAccount] = &[
+    HookAccount::SourceExemption,
+    HookAccount::DestinationExemption,
+];
+
+/// Always in the chain so that pausing never has to rewrite the meta list.
+pub(super) fn is_configured(_config: &MaxWalletConfig) -> bool {
+    true
+}
+
+pub(super) fn check(transfer: &HookTransfer) -> Result<()> {
+    if !transfer.config.paused {
+        return Ok(());
+    }
+    require!(
+        transfer.source_exempt() && transfer.destination_exempt(),
+        MaxWalletError::TransfersPaused
+    );
+    Ok(())
+}
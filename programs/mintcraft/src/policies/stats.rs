@@ -0,0 +1,55 @@
+//! Per-mint activity counters, written after the whole chain passed.
+//!
+//! Opt-in: the stats account is writable in every transfer of the mint, which
+//! serializes them, so it is only in the meta list while `stats_enabled`.
+
+use anchor_lang::prelude::*;
+
+use super::{update_record, Direction, HookAccount, HookTransfer, PolicyKind};
+use crate::{HookStats, MaxWalletConfig};
+
+pub(super) const ACCOUNTS: &[HookAccount] = &[HookAccount::Stats];
+
+pub(super) fn is_configured(config: &MaxWalletConfig) -> bool {
+    config.stats_enabled
+}
+
+/// Counts a transfer in the mint's `HookStats`. Stats never block a transfer:
+/// a missing or foreign account is skipped.
+pub(super) fn check(transfer: &HookTransfer) -> Result<()> {
+    let Some(account) = transfer.account(HookAccount::Stats) else {
+        return Ok(());
+    };
+    if account.owner != &crate::ID || account.data_len() != HookStats::LEN {
+        return Ok(());
+    }
+
+    update_record(account, |stats: &mut HookStats| {
+        if stats.mint != transfer.mint {
+            return Ok(());
+        }
+
+        stats.transfers = stats.transfers.saturating_add(1);
+        stats.volume = stats.volume.saturating_add(transfer.amount as u128);
+        stats.largest_transfer = stats.largest_transfer.max(transfer.amount);
+        stats.last_slot = transfer.slot;
+
+        for policy in PolicyKind::ALL {
+            if policy != PolicyKind::Stats && transfer.config.policies & policy.bit() != 0 {
+                let passes = &mut stats.rule_passes[policy as usize];
+                *passes = passes.saturating_add(1);
+            }
+        }
+
+        let (count, volume) = match transfer.direction {
+            Direction::Buy => (&mut stats.buys, Some(&mut stats.buy_volume)),
+            Direction::Sell => (&mut stats.sells, Some(&mut stats.sell_volume)),
+            Direction::WalletToWallet => (&mut stats.wallet_transfers, None),
+        };
+        *count = count.saturating_add(1);
+        if let Some(volume) = volume {
+            *volume = volume.saturating_add(transfer.amount);
+        }
+        Ok(())
+    })
+}
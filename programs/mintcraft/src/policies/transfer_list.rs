@@ -0,0 +1,48 @@
+//! Blocklist/allowlist of wallet owners, backed by `TransferListEntry` records.
+
+use anchor_lang::prelude::*;
+
+use super::{load_owner_record, HookAccount, HookTransfer};
+use crate::{MaxWalletConfig, MaxWalletError, TransferListEntry, TransferPolicy};
+
+pub(super) const ACCOUNTS: &[HookAccount] = &[
+    HookAccount::SourceListEntry,
+    HookAccount::DestinationListEntry,
+];
+
+pub(super) fn is_configured(config: &MaxWalletConfig) -> bool {
+    config.transfer_policy != TransferPolicy::Open
+}
+
+/// The authority is implicitly allowlisted but can still be blocked.
+pub(super) fn check(transfer: &HookTransfer) -> Result<()> {
+    let is_listed = |account: HookAccount, owner: &Pubkey| {
+        load_owner_record::<TransferListEntry>(transfer.account(account))
+            .is_some_and(|entry| entry.mint == transfer.mint && entry.owner == *owner)
+    };
+    let source_listed = is_listed(HookAccount::SourceListEntry, &transfer.source_owner);
+    let destination_listed = is_listed(
+        HookAccount::DestinationListEntry,
+        &transfer.destination_owner,
+    );
+    let authority = &transfer.config.authority;
+
+    match transfer.config.transfer_policy {
+        TransferPolicy::Open => {}
+        TransferPolicy::Blocklist => {
+            require!(!source_listed, MaxWalletError::SourceBlocked);
+            require!(!destination_listed, MaxWalletError::DestinationBlocked);
+        }
+        TransferPolicy::Allowlist => {
+            require!(
+                source_listed || transfer.source_owner == *authority,
+                MaxWalletError::SourceNotAllowed
+            );
+            require!(
+                destination_listed || transfer.destination_owner == *authority,
+                MaxWalletError::DestinationNotAllowed
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,74 @@
+//! Cap on what a non-exempt wallet can send within a rolling window.
+
+use anchor_lang::prelude::*;
+
+use super::{is_bps_limit, update_wallet_state, HookAccount, HookTransfer};
+use crate::{bps_of_supply, MaxWalletConfig, MaxWalletError};
+
+pub(super) const ACCOUNTS: &[HookAccount] =
+    &[HookAccount::SourceExemption, HookAccount::SourceWalletState];
+
+pub(super) fn is_configured(config: &MaxWalletConfig) -> bool {
+    is_bps_limit(config.max_volume_bps)
+}
+
+/// Only the sender's volume is limited.
+pub(super) fn check(transfer: &HookTransfer) -> Result<()> {
+    if !transfer.sender_limited() {
+        return Ok(());
+    }
+
+    let cap = bps_of_supply(transfer.supply, transfer.config.max_volume_bps)?;
+    let window = transfer.config.volume_window();
+    update_wallet_state(
+        transfer.account(HookAccount::SourceWalletState),
+        &transfer.mint,
+        &transfer.source_owner,
+        |state| {
+            let volume = state
+                .rolling_volume(transfer.now, window)
+                .checked_add(transfer.amount as u128)
+                .ok_or(MaxWalletError::NumericalOverflow)?;
+            require!(volume <= cap, MaxWalletError::VolumeLimitExceeded);
+
+            state.roll_window(transfer.now, window);
+            state.window_volume = state
+                .window_volume
+                .checked_add(transfer.amount)
+                .ok_or(MaxWalletError::NumericalOverflow)?;
+            Ok(())
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policies::Direction;
+
+    fn config() -> MaxWalletConfig {
+        let mut config = MaxWalletConfig {
+            max_volume_bps: 100,
+            ..Default::default()
+        };
+        config.sync_policies();
+        config
+    }
+
+    #[test]
+    fn sender_without_wallet_state_is_rejected() {
+        let config = config();
+        assert_eq!(
+            check(&HookTransfer::for_test(&config, 1)).unwrap_err(),
+            MaxWalletError::WalletStateNotInitialized.into()
+        );
+    }
+
+    #[test]
+    fn buys_from_a_pool_need_no_wallet_state() {
+        let config = config();
+        let mut transfer = HookTransfer::for_test(&config, 1);
+        transfer.direction = Direction::Buy;
+        assert!(check(&transfer).is_ok());
+    }
+}
@@ -0,0 +1,398 @@
+//! Compute units a Token-2022 transfer costs with the mintcraft hook, per
+//! policy.
+//!
+//! Needs the SBF build, so it is ignored by default: run `anchor build`, then
+//! `SBF_OUT_DIR=target/deploy cargo test --test compute_budget -- --ignored
+//! --nocapture`. Anchor's CPI helpers are not available to the native
+//! processor, and only SBF execution is metered. Every scenario must pass, and
+//! the measured units are printed and checked against the budgets below. The
+//! downstream hook CPI is not covered, as it needs a second hook program.
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{instruction::Instruction, system_instruction, system_program, sysvar},
+    InstructionData, ToAccountMetas,
+};
+use anchor_spl::associated_token::{
+    get_associated_token_address_with_program_id,
+    spl_associated_token_account::instruction::create_associated_token_account,
+};
+use mintcraft::{MaxWalletConfigUpdate, TransferPolicy};
+use solana_keypair::Keypair;
+use solana_program_test::{tokio, BanksClient, ProgramTest, ProgramTestContext};
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use spl_token_2022::{
+    extension::{transfer_hook, ExtensionType},
+    instruction::{initialize_mint2, mint_to},
+    offchain::create_transfer_checked_instruction_with_extra_metas,
+    state::Mint,
+};
+
+const DECIMALS: u8 = 6;
+const SUPPLY: u64 = 1_000_000_000;
+const AMOUNT: u64 = 1_000;
+
+// Provisional ceilings, not yet calibrated against an SBF run: replace them
+// with the printed figures plus headroom once measured.
+/// Whole `transfer_checked`, Token-2022 included, with only the always-on
+/// pause policy.
+const BASELINE_BUDGET: u64 = 40_000;
+/// Extra units one policy may add on top of the baseline.
+const POLICY_BUDGET: u64 = 12_000;
+
+fn config_pda(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"max-wallet-config", mint.as_ref()], &mintcraft::ID).0
+}
+
+fn extra_metas_pda(mint: &Pubkey) -> Pubkey {
+    spl_transfer_hook_interface::get_extra_account_metas_address(mint, &mintcraft::ID)
+}
+
+fn ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, &spl_token_2022::id())
+}
+
+struct Harness {
+    context: ProgramTestContext,
+    mint: Pubkey,
+    sender: Keypair,
+    receiver: Pubkey,
+}
+
+impl Harness {
+    async fn new() -> Self {
+        let mut program_test = ProgramTest::new("mintcraft", mintcraft::ID, None);
+        program_test.prefer_bpf(true);
+        let context = program_test.start_with_context().await;
+
+        let mint = Keypair::new();
+        let mut harness = Harness {
+            context,
+            mint: mint.pubkey(),
+            sender: Keypair::new(),
+            receiver: Pubkey::new_unique(),
+        };
+        let payer = harness.payer();
+        let mint_key = harness.mint;
+
+        let space =
+            ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferHook])
+                .unwrap();
+        let rent = harness.banks().get_rent().await.unwrap();
+        harness
+            .send(
+                &[
+                    system_instruction::create_account(
+                        &payer,
+                        &mint_key,
+                        rent.minimum_balance(space),
+                        space as u64,
+                        &spl_token_2022::id(),
+                    ),
+                    transfer_hook::instruction::initialize(
+                        &spl_token_2022::id(),
+                        &mint_key,
+                        Some(payer),
+                        Some(mintcraft::ID),
+                    )
+                    .unwrap(),
+                    initialize_mint2(&spl_token_2022::id(), &mint_key, &payer, None, DECIMALS)
+                        .unwrap(),
+                ],
+                &[&mint],
+            )
+            .await;
+
+        let initialize_config = Instruction {
+            program_id: mintcraft::ID,
+            accounts: mintcraft::accounts::InitializeMaxWalletConfig {
+                payer,
+                authority: payer,
+                mint: mint_key,
+                config: config_pda(&mint_key),
+                extra_account_metas: extra_metas_pda(&mint_key),
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            }
+            .to_account_metas(None),
+            data: mintcraft::instruction::InitializeMaxWalletConfig { max_wallet_bps: 0 }.data(),
+        };
+        let sender = harness.sender.pubkey();
+        let receiver = harness.receiver;
+        let token_program = spl_token_2022::id();
+        harness
+            .send(
+                &[
+                    initialize_config,
+                    create_associated_token_account(&payer, &sender, &mint_key, &token_program),
+                    create_associated_token_account(&payer, &receiver, &mint_key, &token_program),
+                    mint_to(
+                        &token_program,
+                        &mint_key,
+                        &ata(&sender, &mint_key),
+                        &payer,
+                        &[],
+                        SUPPLY,
+                    )
+                    .unwrap(),
+                ],
+                &[],
+            )
+            .await;
+        harness
+    }
+
+    fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    fn banks(&self) -> &BanksClient {
+        &self.context.banks_client
+    }
+
+    async fn send(&mut self, instructions: &[Instruction], signers: &[&Keypair]) {
+        let blockhash = self.banks().get_latest_blockhash().await.unwrap();
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer()),
+            &all_signers,
+            blockhash,
+        );
+        self.banks().process_transaction(transaction).await.unwrap();
+    }
+
+    async fn update(&mut self, update: MaxWalletConfigUpdate) {
+        let payer = self.payer();
+        let instruction = Instruction {
+            program_id: mintcraft::ID,
+            accounts: mintcraft::accounts::UpdateMaxWalletConfig {
+                payer,
+                authority: payer,
+                mint: self.mint,
+                config: config_pda(&self.mint),
+                extra_account_metas: extra_metas_pda(&self.mint),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: mintcraft::instruction::UpdateMaxWalletConfig { update }.data(),
+        };
+        self.send(&[instruction], &[]).await;
+    }
+
+    /// Units consumed by a simulated sender-to-receiver transfer, which must pass.
+    async fn transfer_units(&mut self) -> u64 {
+        let banks = self.banks().clone();
+        let transfer = create_transfer_checked_instruction_with_extra_metas(
+            &spl_token_2022::id(),
+            &ata(&self.sender.pubkey(), &self.mint),
+            &self.mint,
+            &ata(&self.receiver, &self.mint),
+            &self.sender.pubkey(),
+            &[],
+            AMOUNT,
+            DECIMALS,
+            |address| {
+                let banks = banks.clone();
+                async move {
+                    Ok(banks
+                        .get_account(address)
+                        .await?
+                        .map(|account| account.data))
+                }
+            },
+        )
+        .await
+        .unwrap();
+
+        let blockhash = self.banks().get_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            &[transfer],
+            Some(&self.payer()),
+            &[&self.context.payer, &self.sender],
+            blockhash,
+        );
+        let simulation = self
+            .banks()
+            .simulate_transaction(transaction)
+            .await
+            .unwrap();
+        let details = simulation.simulation_details.unwrap();
+        assert_eq!(
+            simulation.result,
+            Some(Ok(())),
+            "transfer failed: {:#?}",
+            details.logs
+        );
+        details.units_consumed
+    }
+
+    /// Measures a transfer with `update` applied, then applies `reset`.
+    async fn policy_units(
+        &mut self,
+        name: &str,
+        baseline: u64,
+        update: MaxWalletConfigUpdate,
+        reset: MaxWalletConfigUpdate,
+    ) {
+        self.update(update).await;
+        let units = self.transfer_units().await;
+        self.update(reset).await;
+        check_budget(name, units, baseline + POLICY_BUDGET);
+        println!("{name}: {units} CU (+{})", units.saturating_sub(baseline));
+    }
+}
+
+fn check_budget(name: &str, units: u64, budget: u64) {
+    assert!(
+        units <= budget,
+        "{name}: {units} CU over the {budget} CU budget"
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires SBF build (SBF_OUT_DIR)"]
+async fn execute_stays_within_compute_budget() {
+    let mut harness = Harness::new().await;
+
+    let baseline = harness.transfer_units().await;
+    check_budget("baseline", baseline, BASELINE_BUDGET);
+    println!("baseline: {baseline} CU");
+
+    harness
+        .policy_units(
+            "max_tx",
+            baseline,
+            MaxWalletConfigUpdate {
+                max_tx_bps: Some(5_000),
+                ..Default::default()
+            },
+            MaxWalletConfigUpdate {
+                max_tx_bps: Some(0),
+                ..Default::default()
+            },
+        )
+        .await;
+    harness
+        .policy_units(
+            "max_wallet",
+            baseline,
+            MaxWalletConfigUpdate {
+                max_wallet_bps: Some(5_000),
+                ..Default::default()
+            },
+            MaxWalletConfigUpdate {
+                max_wallet_bps: Some(0),
+                ..Default::default()
+            },
+        )
+        .await;
+    harness
+        .policy_units(
+            "launch",
+            baseline,
+            MaxWalletConfigUpdate {
+                trading_enabled_at: Some(1),
+                ..Default::default()
+            },
+            MaxWalletConfigUpdate {
+                trading_enabled_at: Some(0),
+                ..Default::default()
+            },
+        )
+        .await;
+    harness
+        .policy_units(
+            "transfer_list",
+            baseline,
+            MaxWalletConfigUpdate {
+                transfer_policy: Some(TransferPolicy::Blocklist),
+                ..Default::default()
+            },
+            MaxWalletConfigUpdate {
+                transfer_policy: Some(TransferPolicy::Open),
+                ..Default::default()
+            },
+        )
+        .await;
+
+    // Cooldown and volume write the sender's wallet state
+    let sender = harness.sender.pubkey();
+    let mint = harness.mint;
+    let payer = harness.payer();
+    let initialize_wallet_state = Instruction {
+        program_id: mintcraft::ID,
+        accounts: mintcraft::accounts::InitializeWalletState {
+            payer,
+            mint,
+            config: config_pda(&mint),
+            wallet_state: Pubkey::find_program_address(
+                &[b"wallet-state", mint.as_ref(), sender.as_ref()],
+                &mintcraft::ID,
+            )
+            .0,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: mintcraft::instruction::InitializeWalletState { owner: sender }.data(),
+    };
+    harness.send(&[initialize_wallet_state], &[]).await;
+
+    harness
+        .policy_units(
+            "cooldown",
+            baseline,
+            MaxWalletConfigUpdate {
+                cooldown_secs: Some(60),
+                ..Default::default()
+            },
+            MaxWalletConfigUpdate {
+                cooldown_secs: Some(0),
+                ..Default::default()
+            },
+        )
+        .await;
+    harness
+        .policy_units(
+            "volume",
+            baseline,
+            MaxWalletConfigUpdate {
+                max_volume_bps: Some(5_000),
+                ..Default::default()
+            },
+            MaxWalletConfigUpdate {
+                max_volume_bps: Some(0),
+                ..Default::default()
+            },
+        )
+        .await;
+
+    // Stats write the per-mint counters once initialized
+    let initialize_stats = Instruction {
+        program_id: mintcraft::ID,
+        accounts: mintcraft::accounts::InitializeHookStats {
+            payer,
+            authority: payer,
+            mint,
+            config: config_pda(&mint),
+            stats: Pubkey::find_program_address(&[b"hook-stats", mint.as_ref()], &mintcraft::ID).0,
+            extra_account_metas: extra_metas_pda(&mint),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: mintcraft::instruction::InitializeHookStats {}.data(),
+    };
+    harness.send(&[initialize_stats], &[]).await;
+    let units = harness.transfer_units().await;
+    check_budget("stats", units, baseline + POLICY_BUDGET);
+    println!("stats: {units} CU (+{})", units.saturating_sub(baseline));
+    harness
+        .update(MaxWalletConfigUpdate {
+            stats_enabled: Some(false),
+            ..Default::default()
+        })
+        .await;
+    assert!(harness.transfer_units().await < units);
+}
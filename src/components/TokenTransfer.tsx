@@ -296,6 +296,7 @@ export function TokenTransfer() {
       setIsUpdatingCap(true);
 
       const instruction = createUpdateMaxWalletConfigInstruction({
+        payer: publicKey,
         authority: publicKey,
         mint: mintDetails.mint,
         maxWalletBps,
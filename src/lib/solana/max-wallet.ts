@@ -10,6 +10,9 @@ import {
 
 const MAX_WALLET_CONFIG_SEED = 'max-wallet-config';
 const EXTRA_ACCOUNT_METAS_SEED = 'extra-account-metas';
+const WALLET_STATE_SEED = 'wallet-state';
+const POOL_ACCOUNT_SEED = 'pool-account';
+const HOOK_STATS_SEED = 'hook-stats';
 export const MAX_WALLET_BPS_CAP = 10_000;
 
 export const MAX_WALLET_HOOK_PROGRAM_ID = new PublicKey(
@@ -18,7 +21,19 @@ export const MAX_WALLET_HOOK_PROGRAM_ID = new PublicKey(
 
 const INIT_MAX_WALLET_CONFIG_INSTRUCTION = 'initialize_max_wallet_config';
 const UPDATE_MAX_WALLET_CONFIG_INSTRUCTION = 'update_max_wallet_config';
+const MIGRATE_MAX_WALLET_CONFIG_INSTRUCTION = 'migrate_max_wallet_config';
+const INIT_WALLET_STATE_INSTRUCTION = 'initialize_wallet_state';
+const PAUSE_INSTRUCTION = 'pause';
+const UNPAUSE_INSTRUCTION = 'unpause';
+const SET_DOWNSTREAM_HOOK_INSTRUCTION = 'set_downstream_hook';
+const ADD_POOL_ACCOUNT_INSTRUCTION = 'add_pool_account';
+const REMOVE_POOL_ACCOUNT_INSTRUCTION = 'remove_pool_account';
+const INIT_HOOK_STATS_INSTRUCTION = 'initialize_hook_stats';
 const MAX_WALLET_CONFIG_ACCOUNT = 'MaxWalletConfig';
+const WALLET_STATE_ACCOUNT = 'WalletState';
+const HOOK_STATS_ACCOUNT = 'HookStats';
+const DEFAULT_VOLUME_WINDOW_SECS = 86_400;
+export const MAX_WALLET_CONFIG_VERSION = 2;
 
 const getInstructionDiscriminator = (name: string): Buffer => {
   const hash = sha256(utf8ToBytes(`global:${name}`));
@@ -41,6 +56,30 @@ export const getExtraAccountMetasPda = (
     MAX_WALLET_HOOK_PROGRAM_ID
   );
 
+export const getWalletStatePda = (
+  mint: PublicKey,
+  owner: PublicKey
+): [PublicKey, number] =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from(WALLET_STATE_SEED), mint.toBuffer(), owner.toBuffer()],
+    MAX_WALLET_HOOK_PROGRAM_ID
+  );
+
+export const getPoolAccountPda = (
+  mint: PublicKey,
+  tokenAccount: PublicKey
+): [PublicKey, number] =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from(POOL_ACCOUNT_SEED), mint.toBuffer(), tokenAccount.toBuffer()],
+    MAX_WALLET_HOOK_PROGRAM_ID
+  );
+
+export const getHookStatsPda = (mint: PublicKey): [PublicKey, number] =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from(HOOK_STATS_SEED), mint.toBuffer()],
+    MAX_WALLET_HOOK_PROGRAM_ID
+  );
+
 export const createInitializeMaxWalletConfigInstruction = (params: {
   payer: PublicKey;
   authority: PublicKey;
@@ -73,27 +112,350 @@ export const createInitializeMaxWalletConfigInstruction = (params: {
   });
 };
 
+const encodeOptionU16 = (value?: number): Buffer => {
+  if (value === undefined) {
+    return Buffer.from([0]);
+  }
+  const buffer = Buffer.alloc(3);
+  buffer.writeUInt8(1, 0);
+  buffer.writeUInt16LE(value, 1);
+  return buffer;
+};
+
+const encodeOptionU8 = (value?: number): Buffer =>
+  value === undefined ? Buffer.from([0]) : Buffer.from([1, value]);
+
+const encodeOptionU32 = (value?: number): Buffer => {
+  if (value === undefined) {
+    return Buffer.from([0]);
+  }
+  const buffer = Buffer.alloc(5);
+  buffer.writeUInt8(1, 0);
+  buffer.writeUInt32LE(value, 1);
+  return buffer;
+};
+
+const encodeOptionU64 = (value?: number | bigint): Buffer => {
+  if (value === undefined) {
+    return Buffer.from([0]);
+  }
+  const buffer = Buffer.alloc(9);
+  buffer.writeUInt8(1, 0);
+  buffer.writeBigUInt64LE(BigInt(value), 1);
+  return buffer;
+};
+
+const encodeOptionI64 = (value?: number | bigint): Buffer => {
+  if (value === undefined) {
+    return Buffer.from([0]);
+  }
+  const buffer = Buffer.alloc(9);
+  buffer.writeUInt8(1, 0);
+  buffer.writeBigInt64LE(BigInt(value), 1);
+  return buffer;
+};
+
+/** Mirrors the on-chain `TransferPolicy` enum */
+export enum TransferPolicy {
+  Open = 0,
+  Blocklist = 1,
+  Allowlist = 2,
+}
+
+/** Bits of `MaxWalletConfig.policies`, in the order the hook runs them */
+export enum PolicyKind {
+  Pause = 1 << 0,
+  Launch = 1 << 1,
+  TransferList = 1 << 2,
+  Cooldown = 1 << 3,
+  Volume = 1 << 4,
+  MaxTx = 1 << 5,
+  MaxWallet = 1 << 6,
+  Direction = 1 << 7,
+  Stats = 1 << 8,
+}
+
 export const createUpdateMaxWalletConfigInstruction = (params: {
+  payer: PublicKey;
   authority: PublicKey;
   mint: PublicKey;
-  maxWalletBps: number;
+  maxWalletBps?: number;
+  maxTxBps?: number;
+  transferPolicy?: TransferPolicy;
+  /** Unix timestamp (seconds) before which only exempt wallets can transfer */
+  tradingEnabledAt?: number | bigint;
+  launchWindowSecs?: number;
+  launchMaxWalletBps?: number;
+  launchMaxTxBps?: number;
+  cooldownSecs?: number;
+  maxVolumeBps?: number;
+  volumeWindowSecs?: number;
+  guardian?: PublicKey;
+  buyMaxTxBps?: number;
+  sellMaxTxBps?: number;
+  buyMaxWalletBps?: number;
+  /** Write-locks the stats account in every transfer while enabled */
+  statsEnabled?: boolean;
+  /** Per-transfer cap in base units, 0 to disable; the lower of this and maxTxBps applies */
+  maxTxAmount?: number | bigint;
 }): TransactionInstruction => {
-  const { authority, mint, maxWalletBps } = params;
+  const {
+    payer,
+    authority,
+    mint,
+    maxWalletBps,
+    maxTxBps,
+    transferPolicy,
+    tradingEnabledAt,
+    launchWindowSecs,
+    launchMaxWalletBps,
+    launchMaxTxBps,
+    cooldownSecs,
+    maxVolumeBps,
+    volumeWindowSecs,
+    guardian,
+    buyMaxTxBps,
+    sellMaxTxBps,
+    buyMaxWalletBps,
+    statsEnabled,
+    maxTxAmount,
+  } = params;
   const [configPda] = getMaxWalletConfigPda(mint);
+  const [extraMetaPda] = getExtraAccountMetasPda(mint);
 
   const discriminator = getInstructionDiscriminator(
     UPDATE_MAX_WALLET_CONFIG_INSTRUCTION
   );
-  const data = Buffer.alloc(discriminator.length + 2);
-  discriminator.copy(data, 0);
-  data.writeUInt16LE(maxWalletBps, discriminator.length);
+  const data = Buffer.concat([
+    discriminator,
+    encodeOptionU16(maxWalletBps),
+    encodeOptionU16(maxTxBps),
+    encodeOptionU8(transferPolicy),
+    encodeOptionI64(tradingEnabledAt),
+    encodeOptionU32(launchWindowSecs),
+    encodeOptionU16(launchMaxWalletBps),
+    encodeOptionU16(launchMaxTxBps),
+    encodeOptionU32(cooldownSecs),
+    encodeOptionU16(maxVolumeBps),
+    encodeOptionU32(volumeWindowSecs),
+    guardian
+      ? Buffer.concat([Buffer.from([1]), guardian.toBuffer()])
+      : Buffer.from([0]),
+    encodeOptionU16(buyMaxTxBps),
+    encodeOptionU16(sellMaxTxBps),
+    encodeOptionU16(buyMaxWalletBps),
+    encodeOptionU8(statsEnabled === undefined ? undefined : Number(statsEnabled)),
+    encodeOptionU64(maxTxAmount),
+  ]);
 
   return new TransactionInstruction({
     programId: MAX_WALLET_HOOK_PROGRAM_ID,
     keys: [
+      { pubkey: payer, isSigner: true, isWritable: true },
       { pubkey: authority, isSigner: true, isWritable: false },
       { pubkey: mint, isSigner: false, isWritable: false },
       { pubkey: configPda, isSigner: false, isWritable: true },
+      { pubkey: extraMetaPda, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    data,
+  });
+};
+
+/**
+ * Realloc a config created by an older program version to the current layout.
+ * Anyone can submit it; `payer` covers the extra rent.
+ */
+export const createMigrateMaxWalletConfigInstruction = (params: {
+  payer: PublicKey;
+  mint: PublicKey;
+}): TransactionInstruction => {
+  const { payer, mint } = params;
+  const [configPda] = getMaxWalletConfigPda(mint);
+  const [extraMetaPda] = getExtraAccountMetasPda(mint);
+
+  return new TransactionInstruction({
+    programId: MAX_WALLET_HOOK_PROGRAM_ID,
+    keys: [
+      { pubkey: payer, isSigner: true, isWritable: true },
+      { pubkey: mint, isSigner: false, isWritable: false },
+      { pubkey: configPda, isSigner: false, isWritable: true },
+      { pubkey: extraMetaPda, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    data: getInstructionDiscriminator(MIGRATE_MAX_WALLET_CONFIG_INSTRUCTION),
+  });
+};
+
+/**
+ * Forward the hook to another transfer-hook program after mintcraft's checks,
+ * or stop forwarding when `downstreamProgram` is omitted.
+ */
+export const createSetDownstreamHookInstruction = (params: {
+  payer: PublicKey;
+  authority: PublicKey;
+  mint: PublicKey;
+  downstreamProgram?: PublicKey;
+}): TransactionInstruction => {
+  const { payer, authority, mint, downstreamProgram } = params;
+  const [configPda] = getMaxWalletConfigPda(mint);
+  const [extraMetaPda] = getExtraAccountMetasPda(mint);
+  // Anchor treats the program id as "None" for optional accounts
+  const downstreamExtraMetas = downstreamProgram
+    ? PublicKey.findProgramAddressSync(
+        [Buffer.from(EXTRA_ACCOUNT_METAS_SEED), mint.toBuffer()],
+        downstreamProgram
+      )[0]
+    : MAX_WALLET_HOOK_PROGRAM_ID;
+
+  const data = Buffer.concat([
+    getInstructionDiscriminator(SET_DOWNSTREAM_HOOK_INSTRUCTION),
+    downstreamProgram
+      ? Buffer.concat([Buffer.from([1]), downstreamProgram.toBuffer()])
+      : Buffer.from([0]),
+  ]);
+
+  return new TransactionInstruction({
+    programId: MAX_WALLET_HOOK_PROGRAM_ID,
+    keys: [
+      { pubkey: payer, isSigner: true, isWritable: true },
+      { pubkey: authority, isSigner: true, isWritable: false },
+      { pubkey: mint, isSigner: false, isWritable: false },
+      { pubkey: configPda, isSigner: false, isWritable: true },
+      { pubkey: extraMetaPda, isSigner: false, isWritable: true },
+      { pubkey: downstreamExtraMetas, isSigner: false, isWritable: false },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    data,
+  });
+};
+
+/** Register an AMM pool token account so the hook can tell buys from sells */
+export const createAddPoolAccountInstruction = (params: {
+  payer: PublicKey;
+  authority: PublicKey;
+  mint: PublicKey;
+  poolTokenAccount: PublicKey;
+}): TransactionInstruction => {
+  const { payer, authority, mint, poolTokenAccount } = params;
+  const [configPda] = getMaxWalletConfigPda(mint);
+  const [poolPda] = getPoolAccountPda(mint, poolTokenAccount);
+  const [extraMetaPda] = getExtraAccountMetasPda(mint);
+
+  return new TransactionInstruction({
+    programId: MAX_WALLET_HOOK_PROGRAM_ID,
+    keys: [
+      { pubkey: payer, isSigner: true, isWritable: true },
+      { pubkey: authority, isSigner: true, isWritable: false },
+      { pubkey: mint, isSigner: false, isWritable: false },
+      { pubkey: configPda, isSigner: false, isWritable: true },
+      { pubkey: poolTokenAccount, isSigner: false, isWritable: false },
+      { pubkey: poolPda, isSigner: false, isWritable: true },
+      { pubkey: extraMetaPda, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    data: getInstructionDiscriminator(ADD_POOL_ACCOUNT_INSTRUCTION),
+  });
+};
+
+export const createRemovePoolAccountInstruction = (params: {
+  authority: PublicKey;
+  mint: PublicKey;
+  poolTokenAccount: PublicKey;
+}): TransactionInstruction => {
+  const { authority, mint, poolTokenAccount } = params;
+  const [configPda] = getMaxWalletConfigPda(mint);
+  const [poolPda] = getPoolAccountPda(mint, poolTokenAccount);
+  const [extraMetaPda] = getExtraAccountMetasPda(mint);
+
+  return new TransactionInstruction({
+    programId: MAX_WALLET_HOOK_PROGRAM_ID,
+    keys: [
+      { pubkey: authority, isSigner: true, isWritable: true },
+      { pubkey: mint, isSigner: false, isWritable: false },
+      { pubkey: configPda, isSigner: false, isWritable: true },
+      { pubkey: poolPda, isSigner: false, isWritable: true },
+      { pubkey: extraMetaPda, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    data: Buffer.concat([
+      getInstructionDiscriminator(REMOVE_POOL_ACCOUNT_INSTRUCTION),
+      poolTokenAccount.toBuffer(),
+    ]),
+  });
+};
+
+/** Create the mint's stats account and turn stats on (authority only) */
+export const createInitializeHookStatsInstruction = (params: {
+  payer: PublicKey;
+  authority: PublicKey;
+  mint: PublicKey;
+}): TransactionInstruction => {
+  const { payer, authority, mint } = params;
+  const [configPda] = getMaxWalletConfigPda(mint);
+  const [statsPda] = getHookStatsPda(mint);
+  const [extraMetaPda] = getExtraAccountMetasPda(mint);
+
+  return new TransactionInstruction({
+    programId: MAX_WALLET_HOOK_PROGRAM_ID,
+    keys: [
+      { pubkey: payer, isSigner: true, isWritable: true },
+      { pubkey: authority, isSigner: true, isWritable: false },
+      { pubkey: mint, isSigner: false, isWritable: false },
+      { pubkey: configPda, isSigner: false, isWritable: true },
+      { pubkey: statsPda, isSigner: false, isWritable: true },
+      { pubkey: extraMetaPda, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    data: getInstructionDiscriminator(INIT_HOOK_STATS_INSTRUCTION),
+  });
+};
+
+/** Pause or unpause all non-exempt transfers (authority or guardian) */
+export const createSetPausedInstruction = (params: {
+  signer: PublicKey;
+  mint: PublicKey;
+  paused: boolean;
+}): TransactionInstruction => {
+  const { signer, mint, paused } = params;
+  const [configPda] = getMaxWalletConfigPda(mint);
+
+  return new TransactionInstruction({
+    programId: MAX_WALLET_HOOK_PROGRAM_ID,
+    keys: [
+      { pubkey: signer, isSigner: true, isWritable: false },
+      { pubkey: mint, isSigner: false, isWritable: false },
+      { pubkey: configPda, isSigner: false, isWritable: true },
+    ],
+    data: getInstructionDiscriminator(
+      paused ? PAUSE_INSTRUCTION : UNPAUSE_INSTRUCTION
+    ),
+  });
+};
+
+/** Wallets need this state before sending while a cooldown or volume limit is set; exempt owners and registered pools don't */
+export const createInitializeWalletStateInstruction = (params: {
+  payer: PublicKey;
+  mint: PublicKey;
+  owner: PublicKey;
+}): TransactionInstruction => {
+  const { payer, mint, owner } = params;
+  const [configPda] = getMaxWalletConfigPda(mint);
+  const [walletStatePda] = getWalletStatePda(mint, owner);
+
+  const data = Buffer.concat([
+    getInstructionDiscriminator(INIT_WALLET_STATE_INSTRUCTION),
+    owner.toBuffer(),
+  ]);
+
+  return new TransactionInstruction({
+    programId: MAX_WALLET_HOOK_PROGRAM_ID,
+    keys: [
+      { pubkey: payer, isSigner: true, isWritable: true },
+      { pubkey: mint, isSigner: false, isWritable: false },
+      { pubkey: configPda, isSigner: false, isWritable: false },
+      { pubkey: walletStatePda, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
     ],
     data,
   });
@@ -122,10 +484,178 @@ export const parseMaxWalletConfig = (data: Buffer) => {
   const authority = new PublicKey(data.slice(offset, offset + 32));
   const maxWalletBps = data.readUInt16LE(offset + 32);
   const bump = data.readUInt8(offset + 34);
+  const maxTxBps =
+    data.length >= offset + 37 ? data.readUInt16LE(offset + 35) : 0;
+  const transferPolicy: TransferPolicy =
+    data.length >= offset + 38 ? data.readUInt8(offset + 37) : TransferPolicy.Open;
+  // 0 for configs that predate versioning and need migrating
+  const version = data.length >= offset + 39 ? data.readUInt8(offset + 38) : 0;
+  const statsEnabled =
+    data.length >= offset + 40 ? data.readUInt8(offset + 39) === 1 : false;
+  const hasLaunchFields = data.length >= offset + 56;
+  const tradingEnabledAt = hasLaunchFields
+    ? Number(data.readBigInt64LE(offset + 40))
+    : 0;
+  const launchWindowSecs = hasLaunchFields ? data.readUInt32LE(offset + 48) : 0;
+  const launchMaxWalletBps = hasLaunchFields
+    ? data.readUInt16LE(offset + 52)
+    : 0;
+  const launchMaxTxBps = hasLaunchFields ? data.readUInt16LE(offset + 54) : 0;
+  const cooldownSecs =
+    data.length >= offset + 60 ? data.readUInt32LE(offset + 56) : 0;
+  const hasVolumeFields = data.length >= offset + 66;
+  const maxVolumeBps = hasVolumeFields ? data.readUInt16LE(offset + 60) : 0;
+  const volumeWindowSecs = hasVolumeFields ? data.readUInt32LE(offset + 62) : 0;
+  const hasPauseFields = data.length >= offset + 99;
+  const guardian = hasPauseFields
+    ? new PublicKey(data.slice(offset + 66, offset + 98))
+    : PublicKey.default;
+  const paused = hasPauseFields ? data.readUInt8(offset + 98) === 1 : false;
+  const policies =
+    data.length >= offset + 101 ? data.readUInt16LE(offset + 99) : 0;
+  const hasDownstreamFields = data.length >= offset + 134;
+  const downstreamHook = hasDownstreamFields
+    ? new PublicKey(data.slice(offset + 101, offset + 133))
+    : PublicKey.default;
+  const downstreamMetaCount = hasDownstreamFields
+    ? data.readUInt8(offset + 133)
+    : 0;
+  const hasDirectionFields = data.length >= offset + 142;
+  const poolCount = hasDirectionFields ? data.readUInt16LE(offset + 134) : 0;
+  const buyMaxTxBps = hasDirectionFields ? data.readUInt16LE(offset + 136) : 0;
+  const sellMaxTxBps = hasDirectionFields ? data.readUInt16LE(offset + 138) : 0;
+  const buyMaxWalletBps = hasDirectionFields
+    ? data.readUInt16LE(offset + 140)
+    : 0;
+  const maxTxAmount =
+    data.length >= offset + 150 ? data.readBigUInt64LE(offset + 142) : BigInt(0);
 
   return {
     authority,
     maxWalletBps,
     bump,
+    maxTxBps,
+    transferPolicy,
+    version,
+    statsEnabled,
+    tradingEnabledAt,
+    launchWindowSecs,
+    launchMaxWalletBps,
+    launchMaxTxBps,
+    cooldownSecs,
+    maxVolumeBps,
+    volumeWindowSecs,
+    guardian,
+    paused,
+    policies,
+    downstreamHook,
+    downstreamMetaCount,
+    poolCount,
+    buyMaxTxBps,
+    sellMaxTxBps,
+    buyMaxWalletBps,
+    maxTxAmount,
+  };
+};
+
+export const parseWalletState = (data: Buffer) => {
+  const expectedDiscriminator = getAccountDiscriminator(WALLET_STATE_ACCOUNT);
+
+  if (data.length < expectedDiscriminator.length + 97) {
+    throw new Error('Wallet state account data is too small');
+  }
+
+  const disc = data.slice(0, expectedDiscriminator.length);
+  if (!disc.equals(expectedDiscriminator)) {
+    throw new Error('Wallet state discriminator mismatch');
+  }
+
+  const offset = expectedDiscriminator.length;
+  return {
+    mint: new PublicKey(data.slice(offset, offset + 32)),
+    owner: new PublicKey(data.slice(offset + 32, offset + 64)),
+    lastTransferAt: Number(data.readBigInt64LE(offset + 64)),
+    bump: data.readUInt8(offset + 72),
+    windowStart: Number(data.readBigInt64LE(offset + 73)),
+    windowVolume: data.readBigUInt64LE(offset + 81),
+    previousWindowVolume: data.readBigUInt64LE(offset + 89),
+  };
+};
+
+/**
+ * Amount a wallet can still send at `now` (unix seconds) under the rolling
+ * volume limit. Mirrors `WalletState::rolling_volume` on-chain.
+ */
+export const getRemainingVolumeAllowance = (params: {
+  config: ReturnType<typeof parseMaxWalletConfig>;
+  walletState: ReturnType<typeof parseWalletState> | null;
+  supply: bigint;
+  now: number;
+}): bigint | null => {
+  const { config, walletState, supply, now } = params;
+  if (config.maxVolumeBps === 0 || config.maxVolumeBps >= MAX_WALLET_BPS_CAP) {
+    return null;
+  }
+
+  const bps = BigInt(config.maxVolumeBps);
+  const cap =
+    (supply * bps + BigInt(MAX_WALLET_BPS_CAP - 1)) /
+    BigInt(MAX_WALLET_BPS_CAP);
+  const limit = cap > 0n ? cap : 1n;
+  if (!walletState || walletState.windowStart === 0) {
+    return limit;
+  }
+
+  const window = config.volumeWindowSecs || DEFAULT_VOLUME_WINDOW_SECS;
+  let windowStart = walletState.windowStart;
+  let current = walletState.windowVolume;
+  let previous = walletState.previousWindowVolume;
+  const elapsed = now - windowStart;
+  if (elapsed >= window * 2) {
+    return limit;
+  }
+  if (elapsed >= window) {
+    windowStart += window;
+    previous = current;
+    current = 0n;
+  }
+
+  const overlap = BigInt(
+    window - Math.max(0, Math.min(now - windowStart, window))
+  );
+  const used = (previous * overlap) / BigInt(window) + current;
+  return used >= limit ? 0n : limit - used;
+};
+
+export const parseHookStats = (data: Buffer) => {
+  const expectedDiscriminator = getAccountDiscriminator(HOOK_STATS_ACCOUNT);
+
+  if (data.length < expectedDiscriminator.length + 177) {
+    throw new Error('Hook stats account data is too small');
+  }
+
+  const disc = data.slice(0, expectedDiscriminator.length);
+  if (!disc.equals(expectedDiscriminator)) {
+    throw new Error('Hook stats discriminator mismatch');
+  }
+
+  const offset = expectedDiscriminator.length;
+  return {
+    mint: new PublicKey(data.slice(offset, offset + 32)),
+    bump: data.readUInt8(offset + 32),
+    buys: data.readBigUInt64LE(offset + 33),
+    sells: data.readBigUInt64LE(offset + 41),
+    walletTransfers: data.readBigUInt64LE(offset + 49),
+    buyVolume: data.readBigUInt64LE(offset + 57),
+    sellVolume: data.readBigUInt64LE(offset + 65),
+    transfers: data.readBigUInt64LE(offset + 73),
+    volume:
+      data.readBigUInt64LE(offset + 81) + (data.readBigUInt64LE(offset + 89) << 64n),
+    largestTransfer: data.readBigUInt64LE(offset + 97),
+    lastSlot: data.readBigUInt64LE(offset + 105),
+    // Indexed by PolicyKind bit position (Stats excluded)
+    rulePasses: Array.from({ length: 8 }, (_, i) =>
+      data.readBigUInt64LE(offset + 113 + i * 8)
+    ),
   };
 };